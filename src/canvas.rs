@@ -1,13 +1,28 @@
 //! In-window drawing canvas for custom primitives like lines, rect and textures
 
+use crate::draw_command::{CommandsList, DrawLayer};
 use crate::ui::WindowContext;
-use crate::{Color, Rect, Vector2, Layout};
+use crate::{Color, Gradient, Layout, Rect, Vector2};
 
 pub struct DrawCanvas<'a> {
     pub(crate) context: WindowContext<'a>,
+    pub(crate) layer: DrawLayer,
 }
 
 impl<'a> DrawCanvas<'a> {
+    /// Draw all following primitives into the given layer of the window
+    pub fn layer(self, layer: DrawLayer) -> Self {
+        DrawCanvas { layer, ..self }
+    }
+
+    fn draw<F: FnOnce(&mut CommandsList)>(&mut self, f: F) {
+        let draw_commands = &mut self.context.window.draw_commands;
+
+        let previous_layer = draw_commands.set_layer(self.layer);
+        f(draw_commands);
+        draw_commands.set_layer(previous_layer);
+    }
+
     pub fn cursor(&self) -> Vector2 {
         let cursor = &self.context.window.cursor;
         Vector2::new(cursor.x, cursor.y)
@@ -28,25 +43,47 @@ impl<'a> DrawCanvas<'a> {
     {
         self.context.register_click_intention(rect);
 
-        self.context
-            .window
-            .draw_commands
-            .draw_rect(rect, stroke, fill);
+        self.draw(|draw_commands| draw_commands.draw_rect(rect, stroke, fill));
+    }
+
+    pub fn rect_gradient(&mut self, rect: Rect, gradient: Gradient) {
+        self.context.register_click_intention(rect);
+
+        self.draw(|draw_commands| draw_commands.draw_rect_gradient(rect, gradient));
     }
 
     pub fn line(&mut self, start: Vector2, end: Vector2, color: Color) {
-        self.context
-            .window
-            .draw_commands
-            .draw_line(start, end, color);
+        self.draw(|draw_commands| draw_commands.draw_line(start, end, color));
+    }
+
+    /// Clip everything drawn until the matching `pop_clip`.
+    /// The rect is intersected with the window's current clipping zone,
+    /// so nothing will be drawn outside of the window.
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.context.window.draw_commands.push_clip(rect);
+    }
+
+    pub fn pop_clip(&mut self) {
+        self.context.window.draw_commands.pop_clip();
+    }
+
+    /// Current clipping zone, `None` means no clipping at all
+    pub fn clip_rect(&self) -> Option<Rect> {
+        self.context.window.draw_commands.clipping_zone
     }
 
     pub fn image(&mut self, rect: Rect, texture: u32) {
         self.context.register_click_intention(rect);
 
-        self.context
-            .window
-            .draw_commands
-            .draw_raw_texture(rect, texture);
+        self.draw(|draw_commands| draw_commands.draw_raw_texture(rect, texture));
+    }
+
+    /// Reserve the rect to be drawn by the backend.
+    /// `DrawList` with `custom` field set will appear in the output of `Ui::render`
+    /// exactly in the place of this call in the drawing order, with the proper clipping.
+    pub fn custom(&mut self, rect: Rect, id: u64) {
+        self.context.register_click_intention(rect);
+
+        self.draw(|draw_commands| draw_commands.draw_custom(rect, id));
     }
 }
@@ -1,13 +1,15 @@
-use crate::{Color, Rect, Vector2};
+use crate::{
+    font::{FontId, Fonts},
+    Color, Gradient, Rect, Vector2,
+};
 
-use miniquad_text_rusttype::FontAtlas;
-
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 #[derive(Debug, Clone)]
 pub(crate) enum DrawCommand {
     DrawCharacter {
         dest: Rect,
+        /// In font atlas pixels, normalized during `render_command`
         source: Rect,
         color: Color,
     },
@@ -16,6 +18,10 @@ pub(crate) enum DrawCommand {
         stroke: Option<Color>,
         fill: Option<Color>,
     },
+    DrawRectGradient {
+        rect: Rect,
+        gradient: Gradient,
+    },
     DrawTriangle {
         p0: Vector2,
         p1: Vector2,
@@ -31,6 +37,11 @@ pub(crate) enum DrawCommand {
         rect: Rect,
         texture: u32,
     },
+    /// Placeholder for something rendered by the backend itself
+    DrawCustom {
+        rect: Rect,
+        id: u64,
+    },
     Clip {
         rect: Option<Rect>,
     },
@@ -52,11 +63,19 @@ impl DrawCommand {
                 rect: rect.offset(offset),
                 texture,
             },
+            DrawCommand::DrawCustom { rect, id } => DrawCommand::DrawCustom {
+                rect: rect.offset(offset),
+                id,
+            },
             DrawCommand::DrawRect { rect, stroke, fill } => DrawCommand::DrawRect {
                 rect: rect.offset(offset),
                 stroke,
                 fill,
             },
+            DrawCommand::DrawRectGradient { rect, gradient } => DrawCommand::DrawRectGradient {
+                rect: rect.offset(offset),
+                gradient,
+            },
             DrawCommand::DrawLine { start, end, color } => DrawCommand::DrawLine {
                 start: start + offset,
                 end: end + offset,
@@ -74,116 +93,253 @@ impl DrawCommand {
         }
     }
 
-    pub(crate) fn estimate_triangles_budget(&self) -> (usize, usize) {
+    /// Same command in pixels `scale` times smaller. Characters are snapped to the pixel grid,
+    /// their glyphs are already rasterized with the matching size.
+    pub fn scale(&self, scale: f32) -> DrawCommand {
+        let point = |point: Vector2| Vector2::new(point.x * scale, point.y * scale);
+
+        match self.clone() {
+            DrawCommand::DrawCharacter {
+                dest,
+                source,
+                color,
+            } => {
+                let dest = dest.scale(scale);
+                DrawCommand::DrawCharacter {
+                    dest: Rect::new(dest.x.round(), dest.y.round(), dest.w, dest.h),
+                    source,
+                    color,
+                }
+            }
+            DrawCommand::DrawRawTexture { rect, texture } => DrawCommand::DrawRawTexture {
+                rect: rect.scale(scale),
+                texture,
+            },
+            DrawCommand::DrawCustom { rect, id } => DrawCommand::DrawCustom {
+                rect: rect.scale(scale),
+                id,
+            },
+            DrawCommand::DrawRect { rect, stroke, fill } => DrawCommand::DrawRect {
+                rect: rect.scale(scale),
+                stroke,
+                fill,
+            },
+            DrawCommand::DrawRectGradient { rect, gradient } => DrawCommand::DrawRectGradient {
+                rect: rect.scale(scale),
+                gradient,
+            },
+            DrawCommand::DrawLine { start, end, color } => DrawCommand::DrawLine {
+                start: point(start),
+                end: point(end),
+                color,
+            },
+            DrawCommand::DrawTriangle { p0, p1, p2, color } => DrawCommand::DrawTriangle {
+                p0: point(p0),
+                p1: point(p1),
+                p2: point(p2),
+                color,
+            },
+            DrawCommand::Clip { rect } => DrawCommand::Clip {
+                rect: rect.map(|rect| rect.scale(scale)),
+            },
+        }
+    }
+
+    /// Exact amount of vertices and indices this command will produce in a `DrawList`
+    pub(crate) fn triangles_budget(&self) -> (usize, usize) {
+        match self {
+            DrawCommand::DrawCharacter { .. } => (4, 6),
+            DrawCommand::DrawRawTexture { .. } => (4, 6),
+            DrawCommand::DrawRect { stroke, fill, .. } => {
+                let fill = if fill.is_some() { 1 } else { 0 };
+                // stroke is drawn as 4 thin rectangles
+                let stroke = if stroke.is_some() { 4 } else { 0 };
+
+                ((fill + stroke) * 4, (fill + stroke) * 6)
+            }
+            DrawCommand::DrawRectGradient { .. } => (4, 6),
+            DrawCommand::DrawLine { .. } => (4, 6),
+            DrawCommand::DrawTriangle { .. } => (3, 3),
+            _ => (0, 0),
+        }
+    }
+}
+
+/// Each window draws into three separate command streams.
+/// Background is rendered below the window's content, foreground - above
+/// the content and all the child windows.
+/// For the root window background and foreground are global:
+/// below and above all the windows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DrawLayer {
+    Background,
+    Content,
+    Foreground,
+}
+
+impl DrawLayer {
+    fn index(self) -> usize {
         match self {
-            DrawCommand::DrawCharacter { .. } => (10, 10),
-            DrawCommand::DrawRawTexture { .. } => (10, 10),
-            DrawCommand::DrawRect { .. } => (10, 10),
-            DrawCommand::DrawLine { .. } => (10, 10),
-            DrawCommand::DrawTriangle { .. } => (10, 10),
-            _ => (0, 0)
+            DrawLayer::Background => 0,
+            DrawLayer::Content => 1,
+            DrawLayer::Foreground => 2,
         }
     }
 }
 
 pub(crate) struct CommandsList {
-    pub commands: Vec<DrawCommand>,
+    layers: [Vec<DrawCommand>; 3],
+    layer: DrawLayer,
+    /// Clip rect last emitted to each layer, `None` if nothing was emitted yet
+    layers_clip: [Option<Option<Rect>>; 3],
+    /// Current clipping rect, intersection of everything pushed with `push_clip`
     pub clipping_zone: Option<Rect>,
-    font_atlas: Rc<FontAtlas>,
+    /// Zones to restore on `pop_clip`
+    clip_stack: Vec<Option<Rect>>,
+    fonts: Rc<RefCell<Fonts>>,
 }
 
 impl CommandsList {
-    pub fn new(font_atlas: Rc<FontAtlas>) -> CommandsList {
+    pub fn new(fonts: Rc<RefCell<Fonts>>) -> CommandsList {
         CommandsList {
-            commands: vec![],
+            layers: [vec![], vec![], vec![]],
+            layer: DrawLayer::Content,
+            layers_clip: [None; 3],
             clipping_zone: None,
-            font_atlas,
+            clip_stack: vec![],
+            fonts,
         }
     }
 
     pub fn clear(&mut self) {
-        self.commands.clear();
+        for layer in &mut self.layers {
+            layer.clear();
+        }
+        self.layer = DrawLayer::Content;
+        self.layers_clip = [None; 3];
         self.clipping_zone = None;
+        self.clip_stack.clear();
+    }
+
+    pub fn commands(&self, layer: DrawLayer) -> &[DrawCommand] {
+        &self.layers[layer.index()]
+    }
+
+    /// Layer for all following draw calls, returns the previous one
+    pub fn set_layer(&mut self, layer: DrawLayer) -> DrawLayer {
+        std::mem::replace(&mut self.layer, layer)
     }
 
     fn add_command(&mut self, cmd: DrawCommand) {
-        self.commands.push(cmd);
+        let layer = self.layer.index();
+
+        // each layer is rendered separately, so clipping state is tracked per layer
+        // and emitted right before the first command that needs it
+        if self.layers_clip[layer] != Some(self.clipping_zone) {
+            self.layers_clip[layer] = Some(self.clipping_zone);
+            self.layers[layer].push(DrawCommand::Clip {
+                rect: self.clipping_zone,
+            });
+        }
+
+        self.layers[layer].push(cmd);
     }
 
     /// calculate character horizontal size,
     /// usually used as an advance between current cursor position
     /// and next potential character
     pub fn character_advance(&self, character: char) -> f32 {
-        if let Some(font_data) = self.font_atlas.character_infos.get(&character) {
-            let font_data = font_data.scale(self.font_atlas.font_size as f32);
-            let advance = font_data.left_padding + font_data.size.0 + font_data.right_padding;
-
-            return advance;
-        }
-
-        0.
+        self.fonts.borrow_mut().advance(character)
     }
 
     pub fn label_size(&self, label: &str, multiline: Option<f32>) -> Vector2 {
         let width = label.split('\n').fold(0.0f32, |max_width, line| {
             max_width.max(line.chars().map(|c| self.character_advance(c)).sum::<f32>())
         });
-        let height = multiline.map_or(14., |line_height| {
+        let height = multiline.map_or(self.line_height(), |line_height| {
             line_height * label.split('\n').count() as f32
         });
 
         Vector2::new(width, height)
     }
 
-    /// If character is in font atlas - will return x advance from position to potential next character position
+    /// Lines of the text word wrapped to the given width, see `text::wrap`
+    pub fn wrap_text<'a>(&self, text: &'a str, max_width: f32) -> Vec<&'a str> {
+        crate::text::wrap(text, max_width, |character| {
+            self.character_advance(character)
+        })
+    }
+
+    /// The active font or one of the fallbacks has a real glyph for the character
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.fonts.borrow().has_glyph(character)
+    }
+
+    /// Height of a single line of text in the active font
+    pub fn line_height(&self) -> f32 {
+        self.fonts.borrow().line_height()
+    }
+
+    /// Distance from the top of the line to the baseline in the active font
+    pub fn baseline(&self) -> f32 {
+        self.fonts.borrow().baseline()
+    }
+
+    pub fn push_font(&mut self, font: FontId) {
+        self.fonts.borrow_mut().push(font);
+    }
+
+    pub fn pop_font(&mut self) {
+        self.fonts.borrow_mut().pop();
+    }
+
+    /// Returns x advance from position to potential next character position.
+    /// Glyphs missing in the atlas are rasterized on the first use, so it is always `Some`.
     pub fn draw_character(
         &mut self,
         character: char,
         position: Vector2,
         color: Color,
     ) -> Option<f32> {
-        if let Some(font_data) = self.font_atlas.character_infos.get(&character) {
-            let font_data = font_data.scale(self.font_atlas.font_size as f32);
-
-            let left_coord = font_data.left_padding;
-            // 4.0 cames from lack of understanding of how ttf works
-            // with 4.0 top_coord is a top_coord of any buttons, wich makes a character be drawen like:
-            // (x, y).....................(x + advance, y)
-            // ...........................
-            // (x, y + self.font_size.y)..(x + advance, y + _)
-            let top_coord = self.font_atlas.font_size as f32 - font_data.height_over_line - 4.0;
-
-            let rect = Rect::new(
-                left_coord + position.x,
-                top_coord + position.y,
-                font_data.size.0,
-                font_data.size.1,
-            );
-            if self
-                .clipping_zone
-                .map_or(false, |clip| !clip.overlaps(&rect))
-            {
-                let advance = font_data.left_padding + font_data.size.0 + font_data.right_padding;
-                return Some(advance);
-            }
+        let glyph = self.fonts.borrow_mut().glyph(character);
+        let font_data = glyph.info;
+
+        let left_coord = font_data.left_padding;
+        // 4.0 cames from lack of understanding of how ttf works
+        // with 4.0 top_coord is a top_coord of any buttons, wich makes a character be drawen like:
+        // (x, y).....................(x + advance, y)
+        // ...........................
+        // (x, y + self.font_size.y)..(x + advance, y + _)
+        // for other font sizes it is scaled proportionally to the default 13 pixels font
+        let top_coord = glyph.font_size - font_data.height_over_line - glyph.font_size * 4.0 / 13.0;
+
+        let rect = Rect::new(
+            left_coord + position.x,
+            top_coord + position.y,
+            font_data.size.0,
+            font_data.size.1,
+        );
+        let advance = font_data.left_padding + font_data.size.0 + font_data.right_padding;
 
-            let cmd = DrawCommand::DrawCharacter {
-                dest: rect,
-                source: Rect::new(
-                    font_data.tex_coords.0,
-                    font_data.tex_coords.1,
-                    font_data.tex_size.0,
-                    font_data.tex_size.1,
-                ),
-                color: color,
-            };
-            self.add_command(cmd);
-
-            let advance = font_data.left_padding + font_data.size.0 + font_data.right_padding;
-            Some(advance)
-        } else {
-            None
+        if self
+            .clipping_zone
+            .map_or(false, |clip| !clip.overlaps(&rect))
+        {
+            return Some(advance);
         }
+
+        self.add_command(DrawCommand::DrawCharacter {
+            dest: rect,
+            source: Rect::new(
+                font_data.tex_coords.0,
+                font_data.tex_coords.1,
+                font_data.tex_size.0,
+                font_data.tex_size.1,
+            ),
+            color,
+        });
+
+        Some(advance)
     }
 
     pub fn draw_label<T: Into<LabelParams>>(&mut self, label: &str, position: Vector2, params: T) {
@@ -207,6 +363,44 @@ impl CommandsList {
         }
     }
 
+    /// Single line label aligned inside the rect.
+    /// Text wider than the rect is truncated with an ellipsis, returns `true` in this case.
+    pub fn draw_label_aligned(
+        &mut self,
+        label: &str,
+        rect: Rect,
+        aligment: Aligment,
+        vertical_aligment: VerticalAligment,
+        color: Color,
+    ) -> bool {
+        let ellipsis = if self.has_glyph('…') { "…" } else { "..." };
+        let truncated = crate::text::truncate(label, rect.w, ellipsis, |character| {
+            self.character_advance(character)
+        });
+        let text = truncated.as_deref().unwrap_or(label);
+
+        let size = self.label_size(text, None);
+        let x = match aligment {
+            Aligment::Left => 0.,
+            Aligment::Center => (rect.w - size.x) / 2.,
+            Aligment::Right => rect.w - size.x,
+        };
+        let y = match vertical_aligment {
+            VerticalAligment::Top => 0.,
+            VerticalAligment::Center => (rect.h - size.y) / 2.,
+            VerticalAligment::Bottom => rect.h - size.y,
+        };
+
+        // whole pixel offsets, centered glyphs would be blurry otherwise
+        self.draw_label(
+            text,
+            Vector2::new(rect.x + x.round(), rect.y + y.round()),
+            color,
+        );
+
+        truncated.is_some()
+    }
+
     pub fn draw_raw_texture(&mut self, rect: Rect, texture: u32) {
         if self
             .clipping_zone
@@ -218,6 +412,17 @@ impl CommandsList {
         self.add_command(DrawCommand::DrawRawTexture { rect, texture })
     }
 
+    pub fn draw_custom(&mut self, rect: Rect, id: u64) {
+        if self
+            .clipping_zone
+            .map_or(false, |clip| !clip.overlaps(&rect))
+        {
+            return;
+        }
+
+        self.add_command(DrawCommand::DrawCustom { rect, id })
+    }
+
     pub fn draw_rect<S, T>(&mut self, rect: Rect, stroke: S, fill: T)
     where
         S: Into<Option<Color>>,
@@ -237,6 +442,63 @@ impl CommandsList {
         })
     }
 
+    pub fn draw_rect_gradient(&mut self, rect: Rect, gradient: Gradient) {
+        if self
+            .clipping_zone
+            .map_or(false, |clip| !clip.overlaps(&rect))
+        {
+            return;
+        }
+
+        self.add_command(DrawCommand::DrawRectGradient { rect, gradient })
+    }
+
+    /// Soft shadow around the rect, made of `size` stacked translucent frames
+    /// each one pixel smaller than the previous.
+    /// Nothing is drawn inside the rect itself, so a translucent rect is not darkened.
+    pub fn draw_shadow(&mut self, rect: Rect, size: f32, offset: f32, color: Color) {
+        let steps = size.ceil() as i32;
+        let color = Color::new(color.r, color.g, color.b, color.a / steps as f32);
+
+        let (left, top) = (rect.x, rect.y);
+        let (right, bottom) = (rect.x + rect.w, rect.y + rect.h);
+
+        for step in 0..steps {
+            let grow = (steps - step) as f32;
+            let shadow_left = left + offset - grow;
+            let shadow_top = top + offset - grow;
+            let shadow_right = right + offset + grow;
+            let shadow_bottom = bottom + offset + grow;
+
+            // the part of the shadow outside the rect: full width strips above and below,
+            // and the strips on the sides between them
+            let middle_top = shadow_top.max(top).min(shadow_bottom);
+            let middle_bottom = shadow_bottom.min(bottom).max(middle_top);
+            let strips = [
+                (shadow_left, shadow_top, shadow_right, middle_top),
+                (shadow_left, middle_bottom, shadow_right, shadow_bottom),
+                (
+                    shadow_left,
+                    middle_top,
+                    shadow_right.min(left),
+                    middle_bottom,
+                ),
+                (
+                    shadow_left.max(right),
+                    middle_top,
+                    shadow_right,
+                    middle_bottom,
+                ),
+            ];
+
+            for &(x0, y0, x1, y1) in &strips {
+                if x1 > x0 && y1 > y0 {
+                    self.draw_rect(Rect::new(x0, y0, x1 - x0, y1 - y0), None, color);
+                }
+            }
+        }
+    }
+
     pub fn draw_triangle<T>(&mut self, p0: Vector2, p1: Vector2, p2: Vector2, color: T)
     where
         T: Into<Color>,
@@ -270,25 +532,39 @@ impl CommandsList {
         });
     }
 
-    #[rustfmt::skip]
-    pub fn clip<T: Into<Option<Rect>>>(&mut self, rect: T) {
-        let rect = rect.into();
+    /// Reset the clip stack, usually to the parent's window clipping zone.
+    /// Everything pushed later will be clipped by this rect as well.
+    pub fn set_base_clip(&mut self, rect: Option<Rect>) {
+        self.clip_stack.clear();
+        self.clipping_zone = rect;
+    }
 
-        self.clipping_zone = if let Some(rect) = rect {
-            Some(self.clipping_zone.and_then(|old_rect| old_rect.intersect(rect)).unwrap_or(rect))
-        } else {
-            None
-        };
+    /// Clip everything drawn until the matching `pop_clip` by the given rect
+    /// intersected with the current clipping zone
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.clip_stack.push(self.clipping_zone);
 
+        self.clipping_zone = Some(match self.clipping_zone {
+            Some(old_rect) => old_rect
+                .intersect(rect)
+                .unwrap_or_else(|| Rect::new(rect.x, rect.y, 0., 0.)),
+            None => rect,
+        });
+    }
 
-        self.add_command(DrawCommand::Clip { rect: self.clipping_zone });
+    pub fn pop_clip(&mut self) {
+        if let Some(rect) = self.clip_stack.pop() {
+            self.clipping_zone = rect;
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+/// Horizontal text alignment
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Aligment {
     Left,
     Center,
+    Right,
 }
 
 impl Default for Aligment {
@@ -297,6 +573,19 @@ impl Default for Aligment {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerticalAligment {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl Default for VerticalAligment {
+    fn default() -> VerticalAligment {
+        VerticalAligment::Top
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LabelParams {
     pub color: Color,
@@ -1,9 +1,11 @@
 use crate::draw_command::DrawCommand;
-use crate::types::{Color, Rect};
+use crate::types::{Color, Gradient, Rect};
 use crate::Vector2;
 
-const MAX_VERTICES: usize = 8000;
-const MAX_INDICES: usize = 4000;
+// indices are u16, so a list can address at most u16::MAX + 1 vertices
+const MAX_VERTICES: usize = u16::MAX as usize + 1;
+// never the limit for quads, it only bounds the lists of triangles
+const MAX_INDICES: usize = MAX_VERTICES / 4 * 6;
 
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
@@ -35,12 +37,22 @@ impl Vertex {
     }
 }
 
+/// Area the backend should render by itself, registered with `DrawCanvas::custom`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CustomDraw {
+    pub id: u64,
+    pub rect: Rect,
+}
+
 #[derive(Debug)]
 pub struct DrawList {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u16>,
     pub clipping_zone: Option<Rect>,
     pub texture: Option<u32>,
+    /// If set, the list has no geometry and the backend should draw
+    /// its own content into the rect, respecting `clipping_zone`
+    pub custom: Option<CustomDraw>,
 }
 
 impl DrawList {
@@ -50,6 +62,7 @@ impl DrawList {
             indices: vec![],
             clipping_zone: None,
             texture: None,
+            custom: None,
         }
     }
 
@@ -59,16 +72,17 @@ impl DrawList {
         self.clipping_zone = None;
     }
 
-    pub fn draw_rectangle_lines(&mut self, rect: Rect, color: Color) {
+    pub fn draw_rectangle_lines(&mut self, rect: Rect, thickness: f32, color: Color) {
         let Rect { x, y, w, h } = rect;
+        let t = thickness;
 
-        self.draw_rectangle(Rect { x, y, w, h: 1. }, Rect::new(0., 0., 0., 0.), color);
+        self.draw_rectangle(Rect { x, y, w, h: t }, Rect::new(0., 0., 0., 0.), color);
         self.draw_rectangle(
             Rect {
-                x: x + w - 1.,
-                y: y + 1.,
-                w: 1.,
-                h: h - 2.,
+                x: x + w - t,
+                y: y + t,
+                w: t,
+                h: h - t * 2.,
             },
             Rect::new(0., 0., 0., 0.),
             color,
@@ -76,9 +90,9 @@ impl DrawList {
         self.draw_rectangle(
             Rect {
                 x,
-                y: y + h - 1.,
+                y: y + h - t,
                 w,
-                h: 1.,
+                h: t,
             },
             Rect::new(0., 0., 0., 0.),
             color,
@@ -86,9 +100,9 @@ impl DrawList {
         self.draw_rectangle(
             Rect {
                 x,
-                y: y + 1.,
-                w: 1.,
-                h: h - 2.,
+                y: y + t,
+                w: t,
+                h: h - t * 2.,
             },
             Rect::new(0., 0., 0., 0.),
             color,
@@ -113,6 +127,24 @@ impl DrawList {
             .extend(indices.iter().map(|i| i + indices_offset));
     }
 
+    fn draw_rectangle_gradient(&mut self, rect: Rect, gradient: Gradient) {
+        let Rect { x, y, w, h } = rect;
+        let [top_left, top_right, bottom_right, bottom_left] = gradient.corners();
+
+        let vertices = [
+            Vertex::new(x, y, 0., 0., top_left),
+            Vertex::new(x + w, y, 0., 0., top_right),
+            Vertex::new(x + w, y + h, 0., 0., bottom_right),
+            Vertex::new(x, y + h, 0., 0., bottom_left),
+        ];
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+        let indices_offset = self.vertices.len() as u16;
+        self.vertices.extend_from_slice(&vertices[..]);
+        self.indices
+            .extend(indices.iter().map(|i| i + indices_offset));
+    }
+
     fn draw_triangle(&mut self, p0: Vector2, p1: Vector2, p2: Vector2, color: Color) {
         let vertices = [
             Vertex::new(p0.x, p0.y, 0.0, 0.0, color),
@@ -171,7 +203,13 @@ fn get_active_draw_list<'a, 'b>(
             }
         }
         DrawCommand::DrawRawTexture { texture, .. } => {
-            if last.texture != Some(*texture) {
+            let (vertices, indices) = command.triangles_budget();
+
+            if last.texture != Some(*texture)
+                || last.custom.is_some()
+                || last.vertices.len() + vertices > MAX_VERTICES
+                || last.indices.len() + indices > MAX_INDICES
+            {
                 let clipping_zone = last.clipping_zone;
 
                 draw_lists.push(DrawList {
@@ -181,15 +219,29 @@ fn get_active_draw_list<'a, 'b>(
                 });
             }
         }
+        DrawCommand::DrawCustom { rect, id } => {
+            let clipping_zone = last.clipping_zone;
+
+            draw_lists.push(DrawList {
+                clipping_zone,
+                custom: Some(CustomDraw {
+                    id: *id,
+                    rect: *rect,
+                }),
+                ..DrawList::new()
+            });
+        }
         DrawCommand::DrawCharacter { .. }
         | DrawCommand::DrawLine { .. }
         | DrawCommand::DrawRect { .. }
+        | DrawCommand::DrawRectGradient { .. }
         | DrawCommand::DrawTriangle { .. } => {
-            let (vertices, indices) = command.estimate_triangles_budget();
+            let (vertices, indices) = command.triangles_budget();
 
             if last.texture != None
-                || last.vertices.len() + vertices >= MAX_VERTICES
-                || last.indices.len() + indices >= MAX_INDICES
+                || last.custom.is_some()
+                || last.vertices.len() + vertices > MAX_VERTICES
+                || last.indices.len() + indices > MAX_INDICES
             {
                 let clipping_zone = last.clipping_zone;
 
@@ -203,7 +255,12 @@ fn get_active_draw_list<'a, 'b>(
     draw_lists.last_mut().unwrap()
 }
 
-pub(crate) fn render_command(draw_lists: &mut Vec<DrawList>, command: DrawCommand) {
+pub(crate) fn render_command(
+    draw_lists: &mut Vec<DrawList>,
+    command: DrawCommand,
+    font_texture_size: Vector2,
+    scale: f32,
+) {
     let active_draw_list = get_active_draw_list(draw_lists, &command);
 
     match command {
@@ -215,17 +272,26 @@ pub(crate) fn render_command(draw_lists: &mut Vec<DrawList>, command: DrawComman
                 active_draw_list.draw_rectangle(rect, Rect::new(0., 0., 0., 0.), fill);
             }
             if let Some(stroke) = stroke {
-                active_draw_list.draw_rectangle_lines(rect, stroke);
+                active_draw_list.draw_rectangle_lines(rect, scale, stroke);
             }
         }
+        DrawCommand::DrawRectGradient { rect, gradient } => {
+            active_draw_list.draw_rectangle_gradient(rect, gradient);
+        }
         DrawCommand::DrawLine { start, end, color } => {
-            active_draw_list.draw_line(start.x, start.y, end.x, end.y, 1., color);
+            active_draw_list.draw_line(start.x, start.y, end.x, end.y, scale, color);
         }
         DrawCommand::DrawCharacter {
             dest,
             source,
             color,
         } => {
+            let source = Rect::new(
+                source.x / font_texture_size.x,
+                source.y / font_texture_size.y,
+                source.w / font_texture_size.x,
+                source.h / font_texture_size.y,
+            );
             active_draw_list.draw_rectangle(dest, source, color);
         }
         DrawCommand::DrawRawTexture { rect, .. } => {
@@ -238,5 +304,7 @@ pub(crate) fn render_command(draw_lists: &mut Vec<DrawList>, command: DrawComman
         DrawCommand::DrawTriangle { p0, p1, p2, color } => {
             active_draw_list.draw_triangle(p0, p1, p2, color);
         }
+        // the list was already created with everything backend needs
+        DrawCommand::DrawCustom { .. } => {}
     }
 }
@@ -0,0 +1,440 @@
+//! Fonts registered in the `Ui`.
+//! Glyphs are rasterized on the first use and packed into a single texture shared by all
+//! the fonts, so the backend still deals with one font texture.
+
+use miniquad_text_rusttype::{AtlasCharacterInfos, CharacterInfos, FontAtlas, Texture};
+
+use std::{collections::HashMap, rc::Rc};
+
+/// Handle of a font loaded with `Ui::load_font`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FontId(usize);
+
+#[derive(Debug)]
+pub enum FontError {
+    /// The data is not a valid TTF/OTF font
+    InvalidData,
+}
+
+impl std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FontError::InvalidData => write!(f, "Invalid font data"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// Empty space between glyphs in the atlas, prevents sampling the neighbours
+const MARGIN: u32 = 2;
+
+struct Font {
+    face: usize,
+    size: u32,
+    /// Glyph metrics in pixels, texture coordinates are in atlas pixels as well
+    glyphs: HashMap<char, CharacterInfos>,
+}
+
+impl Font {
+    fn line_height(&self) -> f32 {
+        // 14 pixels lines for the 13 pixels default font
+        (self.size as f32 * 14. / 13.).round()
+    }
+}
+
+/// Glyph of the active font, in pixels.
+/// `tex_coords` and `tex_size` are in atlas pixels too:
+/// the atlas may grow during the frame, so they are normalized only in `Ui::render`.
+pub(crate) struct Glyph {
+    pub info: CharacterInfos,
+    pub font_size: f32,
+}
+
+/// Shelf packer over a growing RGBA texture
+struct AtlasTexture {
+    texture: Texture,
+    cursor: (u32, u32),
+    row_height: u32,
+    /// Part changed since it was last copied to the `FontAtlas`: left, top, right, bottom
+    dirty: Option<(u32, u32, u32, u32)>,
+}
+
+impl AtlasTexture {
+    fn new(width: u32, height: u32) -> AtlasTexture {
+        let mut texture = Texture {
+            data: vec![0; (width * height * 4) as usize],
+            width,
+            height,
+        };
+
+        // untextured primitives are drawn with (0, 0) texture coordinates,
+        // so the top left corner of the atlas should be white
+        for (x, y) in [(0, 0), (1, 0), (1, 1), (0, 1)].iter() {
+            let start = ((x + width * y) * 4) as usize;
+            texture.data[start..start + 4].copy_from_slice(&[255, 255, 255, 255]);
+        }
+
+        AtlasTexture {
+            texture,
+            cursor: (2 + MARGIN, 0),
+            row_height: 2,
+            dirty: Some((0, 0, width, height)),
+        }
+    }
+
+    fn mark_dirty(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        let (left, top, right, bottom) = self.dirty.unwrap_or((x, y, x + width, y + height));
+
+        self.dirty = Some((
+            left.min(x),
+            top.min(y),
+            right.max(x + width),
+            bottom.max(y + height),
+        ));
+    }
+
+    fn grow(&mut self, min_width: u32) {
+        let old = std::mem::replace(
+            &mut self.texture,
+            Texture {
+                data: vec![],
+                width: 0,
+                height: 0,
+            },
+        );
+        let mut width = old.width;
+        while width < min_width {
+            width *= 2;
+        }
+        let height = old.height * 2;
+
+        let mut data = vec![0; (width * height * 4) as usize];
+        let row_size = old.width as usize * 4;
+        for (y, row) in old.data.chunks_exact(row_size).enumerate() {
+            let start = y * width as usize * 4;
+            data[start..start + row_size].copy_from_slice(row);
+        }
+
+        self.texture = Texture {
+            data,
+            width,
+            height,
+        };
+        self.dirty = Some((0, 0, width, height));
+    }
+
+    /// Copy the coverage bitmap into the atlas, returns its top left corner
+    fn insert(&mut self, width: u32, height: u32, coverage: &[u8]) -> (u32, u32) {
+        if self.cursor.0 + width + MARGIN > self.texture.width {
+            self.cursor = (MARGIN, self.cursor.1 + self.row_height + MARGIN);
+            self.row_height = 0;
+        }
+        while self.cursor.0 + width + MARGIN > self.texture.width
+            || self.cursor.1 + height + MARGIN > self.texture.height
+        {
+            self.grow(self.cursor.0 + width + MARGIN);
+        }
+
+        let (x, y) = self.cursor;
+        let texture_width = self.texture.width as usize;
+        for (row, line) in coverage.chunks_exact(width as usize).enumerate() {
+            for (column, value) in line.iter().enumerate() {
+                let start = ((y as usize + row) * texture_width + x as usize + column) * 4;
+                self.texture.data[start..start + 4].copy_from_slice(&[*value; 4]);
+            }
+        }
+
+        self.mark_dirty(x, y, width, height);
+        self.cursor.0 += width + MARGIN;
+        self.row_height = self.row_height.max(height);
+
+        (x, y)
+    }
+}
+
+pub(crate) struct Fonts {
+    faces: Vec<rusttype::Font<'static>>,
+    fonts: Vec<Font>,
+    /// Faces to look for glyphs missing in the active font, in order
+    fallbacks: Vec<usize>,
+    stack: Vec<FontId>,
+    /// Glyphs are rasterized this many times bigger than the font size,
+    /// while the metrics stay in unscaled pixels
+    scale: f32,
+    texture: AtlasTexture,
+    revision: u64,
+}
+
+/// Atlas without any texture, to be filled with `Fonts::update_atlas`
+pub(crate) fn empty_atlas() -> Rc<FontAtlas> {
+    Rc::new(FontAtlas {
+        texture: Texture {
+            data: vec![],
+            width: 0,
+            height: 0,
+        },
+        character_infos: AtlasCharacterInfos::new(),
+        font_size: 0,
+    })
+}
+
+impl Fonts {
+    pub fn new() -> Fonts {
+        let mut fonts = Fonts {
+            faces: vec![],
+            fonts: vec![],
+            fallbacks: vec![],
+            stack: vec![],
+            scale: 1.,
+            texture: AtlasTexture::new(256, 256),
+            revision: 0,
+        };
+
+        fonts
+            .load(&include_bytes!("../assets/ProggyClean.ttf")[..], 13)
+            .unwrap();
+        fonts.preload_ascii();
+
+        fonts
+    }
+
+    fn preload_ascii(&mut self) {
+        // most of the widgets will need ascii anyway, no reason to wait for them
+        for character in (32u8..127).map(char::from) {
+            self.font_glyph(self.default_font(), character);
+        }
+    }
+
+    /// Forget all the glyphs, they will be rasterized again on the next use.
+    /// The atlas texture starts from scratch, so the old glyphs do not waste the space.
+    fn reset_glyphs(&mut self) {
+        self.texture = AtlasTexture::new(256, 256);
+        for font in &mut self.fonts {
+            font.glyphs.clear();
+        }
+        self.preload_ascii();
+    }
+
+    /// Rasterize all the glyphs again for the new scale
+    pub fn set_scale(&mut self, scale: f32) {
+        if scale == self.scale {
+            return;
+        }
+
+        self.scale = scale;
+        self.reset_glyphs();
+    }
+
+    pub fn load(&mut self, bytes: &[u8], size: u32) -> Result<FontId, FontError> {
+        let face =
+            rusttype::Font::from_bytes(bytes.to_vec()).map_err(|_| FontError::InvalidData)?;
+
+        self.faces.push(face);
+        self.fonts.push(Font {
+            face: self.faces.len() - 1,
+            size,
+            glyphs: HashMap::new(),
+        });
+
+        Ok(FontId(self.fonts.len() - 1))
+    }
+
+    /// Look for glyphs missing in every other font in this font's face
+    pub fn add_fallback(&mut self, font: FontId) {
+        let face = self.fonts[font.0].face;
+
+        if !self.fallbacks.contains(&face) {
+            self.fallbacks.push(face);
+
+            // glyphs previously replaced with "missing glyph" may be found now
+            self.reset_glyphs();
+        }
+    }
+
+    /// The font used by `Ui::new`
+    pub fn default_font(&self) -> FontId {
+        FontId(0)
+    }
+
+    /// Bring the atlas up to date with the glyphs rasterized since the last call.
+    /// Only the changed part of the texture is copied, unless the texture grew
+    /// or the atlas is shared and can't be changed in place.
+    pub fn update_atlas(&mut self, atlas: &mut Rc<FontAtlas>) {
+        let (left, top, right, bottom) = match self.texture.dirty.take() {
+            Some(dirty) => dirty,
+            None => return,
+        };
+        self.revision += 1;
+
+        let texture = &self.texture.texture;
+        let default_font = &self.fonts[0];
+        let (width, height) = (texture.width as f32, texture.height as f32);
+        let size = default_font.size as f32;
+
+        // same units as in `FontAtlas::new`: EMs and normalized texture coordinates
+        let character_infos = default_font
+            .glyphs
+            .iter()
+            .map(|(character, info)| {
+                let info = CharacterInfos {
+                    tex_coords: (info.tex_coords.0 / width, info.tex_coords.1 / height),
+                    tex_size: (info.tex_size.0 / width, info.tex_size.1 / height),
+                    size: (info.size.0 / size, info.size.1 / size),
+                    height_over_line: info.height_over_line / size,
+                    left_padding: info.left_padding / size,
+                    right_padding: info.right_padding / size,
+                };
+                (*character, info)
+            })
+            .collect();
+
+        match Rc::get_mut(atlas) {
+            Some(atlas)
+                if atlas.texture.width == texture.width
+                    && atlas.texture.height == texture.height =>
+            {
+                let row_size = texture.width as usize * 4;
+                for y in top as usize..bottom as usize {
+                    let start = y * row_size + left as usize * 4;
+                    let end = y * row_size + right as usize * 4;
+
+                    atlas.texture.data[start..end].copy_from_slice(&texture.data[start..end]);
+                }
+                atlas.character_infos = character_infos;
+                atlas.font_size = default_font.size;
+            }
+            _ => {
+                *atlas = Rc::new(FontAtlas {
+                    texture: Texture {
+                        data: texture.data.clone(),
+                        width: texture.width,
+                        height: texture.height,
+                    },
+                    character_infos,
+                    font_size: default_font.size,
+                })
+            }
+        }
+    }
+
+    /// Incremented each time `update_atlas` changes the texture
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn push(&mut self, font: FontId) {
+        assert!(font.0 < self.fonts.len(), "Unknown font {:?}", font);
+
+        self.stack.push(font);
+    }
+
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    /// Forget fonts pushed without matching `pop`
+    pub fn reset_stack(&mut self) {
+        self.stack.clear();
+    }
+
+    fn active(&self) -> FontId {
+        self.stack.last().copied().unwrap_or_default()
+    }
+
+    pub fn glyph(&mut self, character: char) -> Glyph {
+        self.font_glyph(self.active(), character)
+    }
+
+    /// Horizontal distance to the next character in the active font
+    pub fn advance(&mut self, character: char) -> f32 {
+        let info = self.glyph(character).info;
+
+        info.left_padding + info.size.0 + info.right_padding
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.fonts[self.active().0].line_height()
+    }
+
+    /// Pixel size of the active font
+    pub fn font_size(&self) -> f32 {
+        self.fonts[self.active().0].size as f32
+    }
+
+    /// Distance from the top of the line to the baseline in the active font
+    pub fn baseline(&self) -> f32 {
+        let font_size = self.font_size();
+
+        // same as in `CommandsList::draw_character`
+        font_size - font_size * 4.0 / 13.0
+    }
+
+    /// The active font or one of the fallbacks has a real glyph for the character
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.glyph_face(self.fonts[self.active().0].face, character)
+            .is_some()
+    }
+
+    /// First face with the glyph: the font's own one or one of the fallbacks
+    fn glyph_face(&self, own_face: usize, character: char) -> Option<usize> {
+        std::iter::once(own_face)
+            .chain(self.fallbacks.iter().copied())
+            .find(|face| self.faces[*face].glyph(character).id().0 != 0)
+    }
+
+    fn font_glyph(&mut self, font: FontId, character: char) -> Glyph {
+        let font_size = self.fonts[font.0].size as f32;
+
+        if let Some(info) = self.fonts[font.0].glyphs.get(&character) {
+            return Glyph {
+                info: *info,
+                font_size,
+            };
+        }
+
+        let own_face = self.fonts[font.0].face;
+        // if nobody has the glyph, font's own "missing glyph" is used
+        let face = self.glyph_face(own_face, character).unwrap_or(own_face);
+
+        let info = self.rasterize(face, font_size, character);
+        self.fonts[font.0].glyphs.insert(character, info);
+
+        Glyph { info, font_size }
+    }
+
+    fn rasterize(&mut self, face: usize, size: f32, character: char) -> CharacterInfos {
+        let scale = self.scale;
+        let size = size * scale;
+        let glyph = self.faces[face]
+            .glyph(character)
+            .scaled(rusttype::Scale::uniform(size));
+        let h_metrics = glyph.h_metrics();
+        let glyph = glyph.positioned(rusttype::point(0., 0.));
+
+        let (width, rows, height_over_line, tex_coords) = match glyph.pixel_bounding_box() {
+            Some(bb) => {
+                let (width, rows) = (bb.width() as u32, bb.height() as u32);
+
+                let mut coverage = vec![0; (width * rows) as usize];
+                glyph.draw(|x, y, v| coverage[(y * width + x) as usize] = (v * 255.) as u8);
+
+                let (x, y) = self.texture.insert(width, rows, &coverage);
+
+                (width, rows, -bb.min.y as f32, (x as f32, y as f32))
+            }
+            // glyphs without outline, like space, are empty quads a quarter of size wide
+            None => ((size as u32) / 4, 0, 0., (0., 0.)),
+        };
+
+        CharacterInfos {
+            tex_coords,
+            tex_size: (width as f32, rows as f32),
+            size: (width as f32 / scale, rows as f32 / scale),
+            height_over_line: height_over_line / scale,
+            left_padding: h_metrics.left_side_bearing / scale,
+            right_padding: (h_metrics.advance_width - width as f32 - h_metrics.left_side_bearing)
+                / scale,
+        }
+    }
+}
@@ -18,6 +18,8 @@ pub enum KeyCode {
     C, // copy
     V, // paste
     X, // cut
+    F, // find
+    H, // replace
 }
 
 pub trait InputHandler {
@@ -5,17 +5,26 @@ mod canvas;
 mod clipboard;
 mod draw_command;
 mod draw_list;
+mod font;
 mod hash;
 mod input_handler;
 mod style;
+mod text;
 mod types;
 mod ui;
 
+#[cfg(feature = "raster")]
+pub mod raster;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
 pub mod widgets;
 
 pub use clipboard::ClipboardObject;
-pub use draw_list::{DrawList, Vertex};
+pub use draw_command::{Aligment, DrawLayer, VerticalAligment};
+pub use draw_list::{CustomDraw, DrawList, Vertex};
+pub use font::{FontError, FontId};
 pub use input_handler::{InputHandler, KeyCode};
 pub use style::Style;
-pub use types::{Color, Rect, Vector2};
+pub use text::{LineMetrics, TextMetrics};
+pub use types::{Color, Gradient, Rect, Vector2};
 pub use ui::{Drag, Id, Layout, Ui};
@@ -0,0 +1,419 @@
+//! Software rasterizer for the draw lists produced by `Ui::render`.
+//!
+//! Slow and simple, but works without GPU: intended for headless tests and screenshots.
+//! Available with the `raster` feature.
+//!
+//! ```skip
+//! let mut draw_lists = vec![];
+//! ui.render(&mut draw_lists);
+//!
+//! let mut image = Image::new(800, 600);
+//! image.fill(Color::from_rgb(255, 255, 255));
+//! Rasterizer::new(&ui.font_atlas).render(&draw_lists, &mut image);
+//! ```
+
+use crate::{Color, DrawList, Rect, Vector2, Vertex};
+
+use miniquad_text_rusttype::FontAtlas;
+
+use std::collections::HashMap;
+
+/// RGBA8 image, used both as a render target and as a source for raw textures
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    /// Transparent black image
+    pub fn new(width: u32, height: u32) -> Image {
+        Image {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    pub fn from_rgba(width: u32, height: u32, data: Vec<u8>) -> Image {
+        assert_eq!(data.len(), width as usize * height as usize * 4);
+
+        Image {
+            width,
+            height,
+            data,
+        }
+    }
+
+    pub fn fill(&mut self, color: Color) {
+        let (r, g, b, a) = color.to_rgba();
+
+        for pixel in self.data.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[r, g, b, a]);
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+
+        [
+            self.data[offset],
+            self.data[offset + 1],
+            self.data[offset + 2],
+            self.data[offset + 3],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: [u8; 4]) {
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+
+        self.data[offset..offset + 4].copy_from_slice(&pixel);
+    }
+
+    /// Encode as QOI, "The Quite OK Image Format".
+    /// Lossless and compact for the flat colors of a UI, readable by many image viewers.
+    pub fn to_qoi(&self) -> Vec<u8> {
+        let mut qoi = b"qoif".to_vec();
+        qoi.extend_from_slice(&self.width.to_be_bytes());
+        qoi.extend_from_slice(&self.height.to_be_bytes());
+        // RGBA, sRGB with linear alpha
+        qoi.extend_from_slice(&[4, 0]);
+
+        let mut index = [[0u8; 4]; 64];
+        let mut previous = [0, 0, 0, 255];
+        let mut run = 0;
+
+        for pixel in self.data.chunks_exact(4) {
+            let pixel = [pixel[0], pixel[1], pixel[2], pixel[3]];
+
+            if pixel == previous {
+                run += 1;
+                if run == 62 {
+                    qoi.push(QOI_OP_RUN | (run - 1));
+                    run = 0;
+                }
+                continue;
+            }
+            if run > 0 {
+                qoi.push(QOI_OP_RUN | (run - 1));
+                run = 0;
+            }
+
+            let hash = qoi_hash(pixel);
+            if index[hash] == pixel {
+                qoi.push(QOI_OP_INDEX | hash as u8);
+            } else if pixel[3] != previous[3] {
+                qoi.push(QOI_OP_RGBA);
+                qoi.extend_from_slice(&pixel);
+            } else {
+                let dr = pixel[0].wrapping_sub(previous[0]) as i8;
+                let dg = pixel[1].wrapping_sub(previous[1]) as i8;
+                let db = pixel[2].wrapping_sub(previous[2]) as i8;
+                let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+
+                if [dr, dg, db].iter().all(|d| (-2..2).contains(d)) {
+                    qoi.push(
+                        QOI_OP_DIFF
+                            | ((dr + 2) as u8) << 4
+                            | ((dg + 2) as u8) << 2
+                            | (db + 2) as u8,
+                    );
+                } else if (-32..32).contains(&dg)
+                    && (-8..8).contains(&dr_dg)
+                    && (-8..8).contains(&db_dg)
+                {
+                    qoi.push(QOI_OP_LUMA | (dg + 32) as u8);
+                    qoi.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                } else {
+                    qoi.push(QOI_OP_RGB);
+                    qoi.extend_from_slice(&pixel[..3]);
+                }
+            }
+            index[hash] = pixel;
+            previous = pixel;
+        }
+        if run > 0 {
+            qoi.push(QOI_OP_RUN | (run - 1));
+        }
+
+        qoi.extend_from_slice(&QOI_END);
+        qoi
+    }
+
+    /// Decode a QOI image, like the ones written by `to_qoi`
+    pub fn from_qoi(data: &[u8]) -> Option<Image> {
+        if data.len() < 14 + QOI_END.len() || &data[..4] != b"qoif" {
+            return None;
+        }
+        let width = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        let height = u32::from_be_bytes([data[8], data[9], data[10], data[11]]);
+        let size = width as usize * height as usize * 4;
+
+        let mut pixels = Vec::with_capacity(size);
+        let mut index = [[0u8; 4]; 64];
+        let mut pixel = [0, 0, 0, 255];
+        let mut bytes = data[14..data.len() - QOI_END.len()].iter().copied();
+
+        while pixels.len() < size {
+            let op = bytes.next()?;
+            let mut run = 1;
+
+            match op {
+                QOI_OP_RGB => {
+                    for channel in &mut pixel[..3] {
+                        *channel = bytes.next()?;
+                    }
+                }
+                QOI_OP_RGBA => {
+                    for channel in &mut pixel {
+                        *channel = bytes.next()?;
+                    }
+                }
+                _ => match op & QOI_MASK {
+                    QOI_OP_INDEX => pixel = index[op as usize],
+                    QOI_OP_DIFF => {
+                        pixel[0] = pixel[0].wrapping_add((op >> 4 & 3).wrapping_sub(2));
+                        pixel[1] = pixel[1].wrapping_add((op >> 2 & 3).wrapping_sub(2));
+                        pixel[2] = pixel[2].wrapping_add((op & 3).wrapping_sub(2));
+                    }
+                    QOI_OP_LUMA => {
+                        let next = bytes.next()?;
+                        let dg = (op & 0x3f).wrapping_sub(32);
+
+                        pixel[0] =
+                            pixel[0].wrapping_add(dg.wrapping_add(next >> 4).wrapping_sub(8));
+                        pixel[1] = pixel[1].wrapping_add(dg);
+                        pixel[2] =
+                            pixel[2].wrapping_add(dg.wrapping_add(next & 0xf).wrapping_sub(8));
+                    }
+                    _ => run = (op & 0x3f) as usize + 1,
+                },
+            }
+            index[qoi_hash(pixel)] = pixel;
+
+            for _ in 0..run {
+                pixels.extend_from_slice(&pixel);
+            }
+        }
+
+        if pixels.len() != size {
+            return None;
+        }
+
+        Some(Image::from_rgba(width, height, pixels))
+    }
+}
+
+const QOI_OP_INDEX: u8 = 0x00;
+const QOI_OP_DIFF: u8 = 0x40;
+const QOI_OP_LUMA: u8 = 0x80;
+const QOI_OP_RUN: u8 = 0xc0;
+const QOI_OP_RGB: u8 = 0xfe;
+const QOI_OP_RGBA: u8 = 0xff;
+const QOI_MASK: u8 = 0xc0;
+const QOI_END: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+fn qoi_hash(pixel: [u8; 4]) -> usize {
+    let [r, g, b, a] = pixel;
+
+    (r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11) % 64
+}
+
+#[derive(Clone, Copy)]
+struct Sampler<'a> {
+    width: u32,
+    height: u32,
+    data: &'a [u8],
+}
+
+impl<'a> Sampler<'a> {
+    /// Nearest neighbour sampling with clamp to edge
+    fn sample(&self, u: f32, v: f32) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [1., 1., 1., 1.];
+        }
+
+        let x = ((u * self.width as f32) as i32).clamp(0, self.width as i32 - 1) as usize;
+        let y = ((v * self.height as f32) as i32).clamp(0, self.height as i32 - 1) as usize;
+        let offset = (y * self.width as usize + x) * 4;
+        let texel = &self.data[offset..offset + 4];
+
+        [
+            texel[0] as f32 / 255.,
+            texel[1] as f32 / 255.,
+            texel[2] as f32 / 255.,
+            texel[3] as f32 / 255.,
+        ]
+    }
+}
+
+/// Renders draw lists into an `Image` the same way a GPU backend would:
+/// per-vertex colors multiplied by the texture, alpha blending and scissor clipping.
+pub struct Rasterizer<'a> {
+    font_texture: Sampler<'a>,
+    textures: HashMap<u32, Sampler<'a>>,
+}
+
+impl<'a> Rasterizer<'a> {
+    pub fn new(font_atlas: &'a FontAtlas) -> Rasterizer<'a> {
+        Rasterizer {
+            font_texture: Sampler {
+                width: font_atlas.texture.width,
+                height: font_atlas.texture.height,
+                data: &font_atlas.texture.data,
+            },
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Register an image for the texture id used in `Ui::texture` or `DrawCanvas::image`.
+    /// Unknown textures are rendered as plain white.
+    pub fn texture(mut self, texture: u32, image: &'a Image) -> Self {
+        self.textures.insert(
+            texture,
+            Sampler {
+                width: image.width,
+                height: image.height,
+                data: &image.data,
+            },
+        );
+        self
+    }
+
+    pub fn render(&self, draw_lists: &[DrawList], target: &mut Image) {
+        for draw_list in draw_lists {
+            let sampler = match draw_list.texture {
+                None => self.font_texture,
+                Some(texture) => self.textures.get(&texture).copied().unwrap_or(Sampler {
+                    width: 0,
+                    height: 0,
+                    data: &[],
+                }),
+            };
+
+            let screen = Rect::new(0., 0., target.width as f32, target.height as f32);
+            let clip = match draw_list.clipping_zone {
+                Some(clip) => match clip.intersect(screen) {
+                    Some(clip) => clip,
+                    None => continue,
+                },
+                None => screen,
+            };
+
+            for triangle in draw_list.indices.chunks_exact(3) {
+                draw_triangle(
+                    target,
+                    clip,
+                    sampler,
+                    [
+                        &draw_list.vertices[triangle[0] as usize],
+                        &draw_list.vertices[triangle[1] as usize],
+                        &draw_list.vertices[triangle[2] as usize],
+                    ],
+                );
+            }
+        }
+    }
+}
+
+fn edge(a: Vector2, b: Vector2, p: Vector2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Tie-breaking rule for pixels exactly on the edge.
+/// Two triangles sharing an edge walk it in the opposite directions,
+/// so exactly one of them will own the pixel.
+fn owns_edge(a: Vector2, b: Vector2) -> bool {
+    let dy = b.y - a.y;
+    let dx = b.x - a.x;
+
+    dy > 0. || (dy == 0. && dx < 0.)
+}
+
+fn draw_triangle(target: &mut Image, clip: Rect, sampler: Sampler, vertices: [&Vertex; 3]) {
+    let position = |vertex: &Vertex| Vector2::new(vertex.pos[0], vertex.pos[1]);
+
+    let [v0, mut v1, mut v2] = vertices;
+    let area = edge(position(v0), position(v1), position(v2));
+    if area == 0. {
+        return;
+    }
+    if area < 0. {
+        std::mem::swap(&mut v1, &mut v2);
+    }
+    let area = area.abs();
+    let (p0, p1, p2) = (position(v0), position(v1), position(v2));
+
+    // pixel (x, y) is covered when its center (x + 0.5, y + 0.5) is inside,
+    // centers on the triangle's bounds are resolved later by the edge ownership
+    let pixels = |min: f32, max: f32, clip_min: f32, clip_max: f32| {
+        let start = (min - 0.5).ceil().max((clip_min - 0.5).ceil()).max(0.);
+        let end = ((max - 0.5).floor() + 1.)
+            .min((clip_max - 0.5).ceil())
+            .max(0.);
+
+        start as u32..end as u32
+    };
+    let x_range = pixels(
+        p0.x.min(p1.x).min(p2.x),
+        p0.x.max(p1.x).max(p2.x),
+        clip.left(),
+        clip.right(),
+    );
+    let y_range = pixels(
+        p0.y.min(p1.y).min(p2.y),
+        p0.y.max(p1.y).max(p2.y),
+        clip.top(),
+        clip.bottom(),
+    );
+
+    for y in y_range {
+        for x in x_range.clone() {
+            let p = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+            let w0 = edge(p1, p2, p);
+            let w1 = edge(p2, p0, p);
+            let w2 = edge(p0, p1, p);
+
+            let inside = |w: f32, a: Vector2, b: Vector2| w > 0. || (w == 0. && owns_edge(a, b));
+            if !inside(w0, p1, p2) || !inside(w1, p2, p0) || !inside(w2, p0, p1) {
+                continue;
+            }
+
+            let (w0, w1, w2) = (w0 / area, w1 / area, w2 / area);
+            let interpolate = |n: usize, attribute: fn(&Vertex, usize) -> f32| {
+                attribute(v0, n) * w0 + attribute(v1, n) * w1 + attribute(v2, n) * w2
+            };
+
+            let u = interpolate(0, |vertex, n| vertex.uv[n]);
+            let v = interpolate(1, |vertex, n| vertex.uv[n]);
+            let texel = sampler.sample(u, v);
+
+            let mut source = [0.; 4];
+            for (n, channel) in source.iter_mut().enumerate() {
+                *channel = interpolate(n, |vertex, n| vertex.color[n]) * texel[n];
+            }
+
+            blend(target, x, y, source);
+        }
+    }
+}
+
+fn blend(target: &mut Image, x: u32, y: u32, source: [f32; 4]) {
+    let destination = target.pixel(x, y);
+    let alpha = source[3].clamp(0., 1.);
+
+    let mut result = [0; 4];
+    for ((result, source), destination) in result.iter_mut().zip(&source).zip(&destination).take(3)
+    {
+        let destination = *destination as f32 / 255.;
+        let channel = source * alpha + destination * (1. - alpha);
+
+        *result = (channel.clamp(0., 1.) * 255.).round() as u8;
+    }
+    let destination_alpha = destination[3] as f32 / 255.;
+    result[3] = ((alpha + destination_alpha * (1. - alpha)) * 255.).round() as u8;
+
+    target.set_pixel(x, y, result);
+}
@@ -0,0 +1,201 @@
+//! Golden image testing: drive a `Ui` with scripted input, render it with the
+//! software rasterizer and compare the result with a reference image.
+//! Available with the `snapshot` feature, the crate's own snapshot tests run with
+//! `cargo test --features snapshot`.
+//!
+//! ```skip
+//! let mut harness = Harness::new(200, 100);
+//!
+//! harness.frame(&[], |ui| ui.label(None, "Hello"));
+//! harness.frame(&[Event::MouseMove(Vector2::new(10., 10.))], |ui| ui.label(None, "Hello"));
+//!
+//! assert_snapshot(&harness.render(), "tests/snapshots/hello.qoi", 2);
+//! ```
+//!
+//! Set `MEGAUI_BLESS=1` to write the reference images: the missing ones for the new tests
+//! and the existing ones after an intended change. Without it a missing reference fails the test.
+
+use crate::{
+    raster::{Image, Rasterizer},
+    Color, InputHandler, KeyCode, Ui, Vector2,
+};
+
+use std::path::{Path, PathBuf};
+
+/// Input event fed to the `Ui` right before the frame's ui code.
+#[derive(Debug, Clone)]
+pub enum Event {
+    MouseMove(Vector2),
+    MouseDown(Vector2),
+    MouseUp(Vector2),
+    MouseWheel(f32, f32),
+    Char(char),
+    Key(KeyCode),
+    /// `KeyCode` with shift and ctrl modifiers
+    KeyWithModifiers(KeyCode, bool, bool),
+}
+
+pub struct Harness {
+    ui: Ui,
+    width: u32,
+    height: u32,
+    background: Color,
+    delta: f32,
+}
+
+impl Harness {
+    pub fn new(width: u32, height: u32) -> Harness {
+        Harness {
+            ui: Ui::new(),
+            width,
+            height,
+            background: Color::from_rgb(255, 255, 255),
+            delta: 1. / 60.,
+        }
+    }
+
+    pub fn background(self, background: Color) -> Self {
+        Harness { background, ..self }
+    }
+
+    pub fn ui(&mut self) -> &mut Ui {
+        &mut self.ui
+    }
+
+    /// Start a new frame, apply the events and run the ui code.
+    ///
+    /// Note that windows become visible only on the frame after
+    /// their first `begin`, so the ui code usually should be run at least twice.
+    pub fn frame<F: FnOnce(&mut Ui)>(&mut self, events: &[Event], f: F) {
+        self.ui.new_frame(self.delta);
+
+        for event in events {
+            match event.clone() {
+                Event::MouseMove(position) => self.ui.mouse_move((position.x, position.y)),
+                Event::MouseDown(position) => {
+                    self.ui.mouse_move((position.x, position.y));
+                    self.ui.mouse_down((position.x, position.y));
+                }
+                Event::MouseUp(position) => {
+                    self.ui.mouse_move((position.x, position.y));
+                    self.ui.mouse_up((position.x, position.y));
+                }
+                Event::MouseWheel(x, y) => self.ui.mouse_wheel(x, y),
+                Event::Char(character) => self.ui.char_event(character, false, false),
+                Event::Key(key) => self.ui.key_down(key, false, false),
+                Event::KeyWithModifiers(key, shift, ctrl) => self.ui.key_down(key, shift, ctrl),
+            }
+        }
+
+        f(&mut self.ui);
+    }
+
+    /// Rasterize the last frame
+    pub fn render(&mut self) -> Image {
+        let mut draw_lists = vec![];
+        self.ui.render(&mut draw_lists);
+
+        let mut image = Image::new(self.width, self.height);
+        image.fill(self.background);
+        Rasterizer::new(&self.ui.font_atlas).render(&draw_lists, &mut image);
+
+        image
+    }
+}
+
+#[derive(Debug)]
+pub struct Mismatch {
+    /// Amount of pixels with any channel differing more than the tolerance
+    pub pixels: usize,
+    /// Red on the mismatched pixels, faded actual image elsewhere
+    pub diff: Image,
+}
+
+/// Per-channel comparison, `tolerance` is the biggest allowed difference of a single channel.
+pub fn compare(actual: &Image, expected: &Image, tolerance: u8) -> Result<(), Mismatch> {
+    if actual.width != expected.width || actual.height != expected.height {
+        return Err(Mismatch {
+            pixels: (actual.width * actual.height).max(expected.width * expected.height) as usize,
+            diff: actual.clone(),
+        });
+    }
+
+    let mut pixels = 0;
+    let mut diff = Image::new(actual.width, actual.height);
+
+    for y in 0..actual.height {
+        for x in 0..actual.width {
+            let a = actual.pixel(x, y);
+            let b = expected.pixel(x, y);
+
+            let differs = a
+                .iter()
+                .zip(b.iter())
+                .any(|(a, b)| (*a as i32 - *b as i32).abs() > tolerance as i32);
+
+            if differs {
+                pixels += 1;
+                diff.set_pixel(x, y, [255, 0, 0, 255]);
+            } else {
+                let fade = |channel: u8| 191 + channel / 4;
+                diff.set_pixel(x, y, [fade(a[0]), fade(a[1]), fade(a[2]), 255]);
+            }
+        }
+    }
+
+    if pixels == 0 {
+        Ok(())
+    } else {
+        Err(Mismatch { pixels, diff })
+    }
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    path.with_file_name(format!("{}.{}.qoi", stem, suffix))
+}
+
+/// Compare with the reference image stored at `path`.
+///
+/// On mismatch `<name>.actual.qoi` and `<name>.diff.qoi` are written next to the reference
+/// and the function panics. A missing reference panics as well, unless `MEGAUI_BLESS=1`.
+pub fn assert_snapshot<P: AsRef<Path>>(image: &Image, path: P, tolerance: u8) {
+    let path = path.as_ref();
+    let bless = std::env::var("MEGAUI_BLESS").map_or(false, |bless| bless == "1");
+
+    if bless {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(path, image.to_qoi()).unwrap();
+        return;
+    }
+
+    if !path.exists() {
+        panic!(
+            "No reference image {}, run with MEGAUI_BLESS=1 to create it",
+            path.display()
+        );
+    }
+    let expected = std::fs::read(path)
+        .ok()
+        .and_then(|data| Image::from_qoi(&data))
+        .unwrap_or_else(|| panic!("Can't read reference image {}", path.display()));
+
+    if let Err(mismatch) = compare(image, &expected, tolerance) {
+        let actual_path = sibling(path, "actual");
+        let diff_path = sibling(path, "diff");
+
+        std::fs::write(&actual_path, image.to_qoi()).unwrap();
+        std::fs::write(&diff_path, mismatch.diff.to_qoi()).unwrap();
+
+        panic!(
+            "{} pixels differ from {}, see {} and {}",
+            mismatch.pixels,
+            path.display(),
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
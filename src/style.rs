@@ -1,4 +1,4 @@
-use crate::types::Color;
+use crate::types::{Color, Gradient};
 
 #[derive(Debug, Clone)]
 pub struct Style {
@@ -14,6 +14,12 @@ pub struct Style {
     pub window_background_focused: Color,
     pub window_background_inactive: Color,
 
+    /// Blur radius of the shadow under windows and popups, 0 disables shadows
+    pub window_shadow_size: f32,
+    pub window_shadow_offset: f32,
+    pub window_shadow_focused: Color,
+    pub window_shadow_inactive: Color,
+
     pub editbox_background_focused: Color,
     pub editbox_background_inactive: Color,
 
@@ -35,6 +41,11 @@ pub struct Style {
     pub inactive_title: Color,
     pub focused_title: Color,
 
+    /// Titlebar fill drawn over the window background, a light vertical gradient by default.
+    /// `None` keeps the titlebar plain
+    pub title_background_focused: Option<Gradient>,
+    pub title_background_inactive: Option<Gradient>,
+
     pub focused_text: Color,
     pub inactive_text: Color,
 
@@ -54,6 +65,26 @@ pub struct Style {
     pub editbox_cursor_focused_inactive: Color,
     pub editbox_cursor_inactive: Color,
 
+    /// Hint shown in the empty editbox
+    pub editbox_placeholder_focused: Color,
+    pub editbox_placeholder_inactive: Color,
+
+    /// Gutter with the line numbers of the code editing editboxes
+    pub editbox_line_number_focused: Color,
+    pub editbox_line_number_inactive: Color,
+
+    /// Frame around the bracket under the cursor and the matching one
+    pub editbox_bracket_match_focused: Color,
+    pub editbox_bracket_match_inactive: Color,
+
+    /// Background of the find bar query occurrences
+    pub editbox_find_match_focused: Color,
+    pub editbox_find_match_inactive: Color,
+
+    /// Border and message of the inputs with failed validation
+    pub validation_error_focused: Color,
+    pub validation_error_inactive: Color,
+
     pub separator_focused: Color,
     pub separator_inactive: Color,
 
@@ -72,6 +103,10 @@ pub struct Style {
     pub combobox_border_hovered: Color,
     pub combobox_border_inactive: Color,
 
+    pub tooltip_background: Color,
+    pub tooltip_border: Color,
+    /// Longer tooltips are word wrapped
+    pub tooltip_max_width: f32,
 }
 
 impl Default for Style {
@@ -85,6 +120,10 @@ impl Default for Style {
             window_border_inactive: Color::from_rgba(102, 102, 102, 127),
             window_background_focused: Color::from_rgba(238, 238, 238, 255),
             window_background_inactive: Color::from_rgba(238, 238, 238, 128),
+            window_shadow_size: 0.,
+            window_shadow_offset: 3.,
+            window_shadow_focused: Color::from_rgba(0, 0, 0, 100),
+            window_shadow_inactive: Color::from_rgba(0, 0, 0, 40),
             editbox_background_focused: Color::from_rgba(200, 200, 200, 255),
             editbox_background_inactive: Color::from_rgba(200, 200, 200, 128),
             drag_background_focused: Color::from_rgba(230, 230, 230, 255),
@@ -100,6 +139,14 @@ impl Default for Style {
             scrollbar_background_focused_inactive: Color::from_rgba(204, 204, 204, 128),
             inactive_title: Color::from_rgba(102, 102, 102, 128),
             focused_title: Color::from_rgba(0, 0, 0, 255),
+            title_background_focused: Some(Gradient::vertical(
+                Color::from_rgb(252, 252, 252),
+                Color::from_rgb(214, 214, 214),
+            )),
+            title_background_inactive: Some(Gradient::vertical(
+                Color::from_rgba(252, 252, 252, 128),
+                Color::from_rgba(224, 224, 224, 128),
+            )),
             focused_text: Color::from_rgba(0, 0, 0, 255),
             inactive_text: Color::from_rgba(102, 102, 102, 127),
             margin_button: 3.,
@@ -117,6 +164,21 @@ impl Default for Style {
             editbox_cursor_focused_inactive: Color::from_rgba(220, 220, 220, 235),
             editbox_cursor_inactive: Color::from_rgba(50, 50, 50, 34),
 
+            editbox_placeholder_focused: Color::from_rgba(120, 120, 120, 255),
+            editbox_placeholder_inactive: Color::from_rgba(120, 120, 120, 127),
+
+            editbox_line_number_focused: Color::from_rgba(150, 150, 150, 255),
+            editbox_line_number_inactive: Color::from_rgba(150, 150, 150, 127),
+
+            editbox_bracket_match_focused: Color::from_rgba(90, 120, 200, 255),
+            editbox_bracket_match_inactive: Color::from_rgba(90, 120, 200, 127),
+
+            editbox_find_match_focused: Color::from_rgba(240, 200, 60, 140),
+            editbox_find_match_inactive: Color::from_rgba(240, 200, 60, 60),
+
+            validation_error_focused: Color::from_rgba(200, 30, 30, 255),
+            validation_error_inactive: Color::from_rgba(200, 30, 30, 127),
+
             separator_focused: Color::from_rgba(180, 180, 180, 235),
             separator_inactive: Color::from_rgba(180, 180, 180, 134),
 
@@ -134,6 +196,10 @@ impl Default for Style {
             combobox_border_selected: Color::from_rgba(180, 180, 180, 255),
             combobox_border_hovered: Color::from_rgba(150, 150, 150, 255),
             combobox_border_inactive: Color::from_rgba(255, 255, 255, 0),
+
+            tooltip_background: Color::from_rgba(255, 255, 225, 255),
+            tooltip_border: Color::from_rgba(68, 68, 68, 255),
+            tooltip_max_width: 300.,
         }
     }
 }
@@ -155,6 +221,22 @@ impl Style {
         }
     }
 
+    pub fn title_background(&self, focused: bool) -> Option<Gradient> {
+        if focused {
+            self.title_background_focused
+        } else {
+            self.title_background_inactive
+        }
+    }
+
+    pub fn window_shadow(&self, focused: bool) -> Color {
+        if focused {
+            self.window_shadow_focused
+        } else {
+            self.window_shadow_inactive
+        }
+    }
+
     pub fn editbox_background(&self, focused: bool) -> Color {
         if focused {
             self.editbox_background_focused
@@ -163,6 +245,46 @@ impl Style {
         }
     }
 
+    pub fn editbox_placeholder(&self, focused: bool) -> Color {
+        if focused {
+            self.editbox_placeholder_focused
+        } else {
+            self.editbox_placeholder_inactive
+        }
+    }
+
+    pub fn editbox_line_number(&self, focused: bool) -> Color {
+        if focused {
+            self.editbox_line_number_focused
+        } else {
+            self.editbox_line_number_inactive
+        }
+    }
+
+    pub fn editbox_bracket_match(&self, focused: bool) -> Color {
+        if focused {
+            self.editbox_bracket_match_focused
+        } else {
+            self.editbox_bracket_match_inactive
+        }
+    }
+
+    pub fn editbox_find_match(&self, focused: bool) -> Color {
+        if focused {
+            self.editbox_find_match_focused
+        } else {
+            self.editbox_find_match_inactive
+        }
+    }
+
+    pub fn validation_error(&self, focused: bool) -> Color {
+        if focused {
+            self.validation_error_focused
+        } else {
+            self.validation_error_inactive
+        }
+    }
+
     pub fn checkbox_background(&self, focused: bool, hovered: bool) -> Color {
         if hovered {
             self.checkbox_background_hovered
@@ -0,0 +1,185 @@
+//! Text layout shared by the widgets drawing more than a single line of text
+
+use crate::Vector2;
+
+use std::ops::Range;
+
+/// Layout of a text in the active font, see `Ui::measure_text`
+#[derive(Debug, Clone)]
+pub struct TextMetrics {
+    /// Width of the widest line and the height of all the lines
+    pub size: Vector2,
+    pub lines: Vec<LineMetrics>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LineMetrics {
+    /// Byte range of the line in the text, without the `\n`
+    pub range: Range<usize>,
+    /// Distance from the top of the text to the top of the line
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Distance from the top of the line to the baseline
+    pub baseline: f32,
+    /// X of the caret before each character of the line, and one more after the last one
+    pub carets: Vec<f32>,
+    /// Byte offset in the line of each caret
+    offsets: Vec<usize>,
+}
+
+impl TextMetrics {
+    /// Position of the caret placed before the byte `index` of the text,
+    /// relative to the top left corner of the text.
+    /// Indices past the end of the text are placed after the last character.
+    pub fn caret(&self, index: usize) -> Vector2 {
+        let line = self
+            .lines
+            .iter()
+            .find(|line| index <= line.range.end)
+            .unwrap_or_else(|| self.lines.last().unwrap());
+        let column = line
+            .offsets
+            .iter()
+            .position(|offset| line.range.start + offset >= index)
+            .unwrap_or(line.offsets.len() - 1);
+
+        Vector2::new(line.carets[column], line.y)
+    }
+
+    /// Byte index of the caret closest to the point, relative to the top left corner of the text
+    pub fn index_at(&self, point: Vector2) -> usize {
+        let line = self
+            .lines
+            .iter()
+            .find(|line| point.y < line.y + line.height)
+            .unwrap_or_else(|| self.lines.last().unwrap());
+
+        let column = line
+            .carets
+            .windows(2)
+            .position(|carets| point.x < (carets[0] + carets[1]) / 2.)
+            .unwrap_or(line.carets.len() - 1);
+
+        line.range.start + line.offsets[column]
+    }
+}
+
+/// Metrics of every line of the text, lines are split on `\n` only
+pub(crate) fn measure<F: FnMut(char) -> f32>(
+    text: &str,
+    line_height: f32,
+    baseline: f32,
+    mut advance: F,
+) -> TextMetrics {
+    let mut lines = vec![];
+    let mut start = 0;
+
+    for (n, line) in text.split('\n').enumerate() {
+        let mut carets = vec![0.];
+        let mut offsets = vec![];
+        let mut width = 0.;
+        for (offset, character) in line.char_indices() {
+            offsets.push(offset);
+            width += advance(character);
+            carets.push(width);
+        }
+        offsets.push(line.len());
+
+        lines.push(LineMetrics {
+            range: start..start + line.len(),
+            y: n as f32 * line_height,
+            width,
+            height: line_height,
+            baseline,
+            carets,
+            offsets,
+        });
+        start += line.len() + 1;
+    }
+
+    TextMetrics {
+        size: Vector2::new(
+            lines.iter().fold(0.0f32, |max, line| max.max(line.width)),
+            line_height * lines.len() as f32,
+        ),
+        lines,
+    }
+}
+
+/// Split the text into lines no wider than `max_width`.
+///
+/// Explicit newlines always start a new line. Lines are broken on the last space that fits,
+/// words longer than the whole line are broken on any character.
+/// Spaces the line was broken on are not included in any of the lines.
+pub(crate) fn wrap<F: FnMut(char) -> f32>(text: &str, max_width: f32, mut advance: F) -> Vec<&str> {
+    let mut lines = vec![];
+
+    for paragraph in text.split('\n') {
+        let mut line_start = 0;
+        let mut line_width = 0.;
+        // byte offset of the last space in the current line
+        let mut last_space = None;
+
+        for (i, character) in paragraph.char_indices() {
+            let character_width = advance(character);
+
+            // trailing spaces are allowed to hang over the edge
+            if character == ' ' {
+                last_space = Some(i);
+                line_width += character_width;
+                continue;
+            }
+
+            if line_width + character_width > max_width && i > line_start {
+                match last_space {
+                    Some(space) => {
+                        lines.push(paragraph[line_start..space].trim_end_matches(' '));
+                        line_start = space + 1;
+                        line_width = paragraph[line_start..i].chars().map(&mut advance).sum();
+                    }
+                    None => {
+                        lines.push(&paragraph[line_start..i]);
+                        line_start = i;
+                        line_width = 0.;
+                    }
+                }
+                last_space = None;
+            }
+
+            line_width += character_width;
+        }
+
+        lines.push(&paragraph[line_start..]);
+    }
+
+    lines
+}
+
+/// Shorten the text to fit `max_width` together with the `ellipsis` appended.
+/// `None` if the text fits as is.
+pub(crate) fn truncate<F: FnMut(char) -> f32>(
+    text: &str,
+    max_width: f32,
+    ellipsis: &str,
+    mut advance: F,
+) -> Option<String> {
+    let width: f32 = text.chars().map(&mut advance).sum();
+    if width <= max_width {
+        return None;
+    }
+
+    let mut width: f32 = ellipsis.chars().map(&mut advance).sum();
+    let mut truncated = String::new();
+    for character in text.chars() {
+        width += advance(character);
+        if width > max_width {
+            break;
+        }
+        truncated.push(character);
+    }
+    truncated.truncate(truncated.trim_end().len());
+    truncated.push_str(ellipsis);
+
+    Some(truncated)
+}
@@ -155,6 +155,15 @@ impl Rect {
     pub fn offset(self, offset: Vector2) -> Rect {
         Rect::new(self.x + offset.x, self.y + offset.y, self.w, self.h)
     }
+
+    pub fn scale(self, scale: f32) -> Rect {
+        Rect::new(
+            self.x * scale,
+            self.y * scale,
+            self.w * scale,
+            self.h * scale,
+        )
+    }
 }
 
 /// A RGBA color in the `sRGB` color space represented as `f32`'s in the range `[0.0-1.0]`
@@ -329,3 +338,47 @@ impl Into<String> for Color {
         )
     }
 }
+
+/// Rect fill with colors interpolated between the rect's corners
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Gradient {
+    Vertical {
+        top: Color,
+        bottom: Color,
+    },
+    Horizontal {
+        left: Color,
+        right: Color,
+    },
+    /// Each corner has its own color
+    Corners {
+        top_left: Color,
+        top_right: Color,
+        bottom_right: Color,
+        bottom_left: Color,
+    },
+}
+
+impl Gradient {
+    pub fn vertical(top: Color, bottom: Color) -> Gradient {
+        Gradient::Vertical { top, bottom }
+    }
+
+    pub fn horizontal(left: Color, right: Color) -> Gradient {
+        Gradient::Horizontal { left, right }
+    }
+
+    /// Colors in the clockwise order, starting from the top left corner
+    pub fn corners(&self) -> [Color; 4] {
+        match *self {
+            Gradient::Vertical { top, bottom } => [top, top, bottom, bottom],
+            Gradient::Horizontal { left, right } => [left, right, right, left],
+            Gradient::Corners {
+                top_left,
+                top_right,
+                bottom_right,
+                bottom_left,
+            } => [top_left, top_right, bottom_right, bottom_left],
+        }
+    }
+}
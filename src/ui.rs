@@ -1,10 +1,16 @@
 use crate::{
-    canvas::DrawCanvas, draw_command::CommandsList, draw_list::DrawList, types::Rect,
-    types::Vector2, InputHandler, Style,
+    canvas::DrawCanvas,
+    draw_command::{CommandsList, DrawLayer},
+    draw_list::DrawList,
+    font::{self, FontError, FontId, Fonts},
+    text::{self, TextMetrics},
+    types::Rect,
+    types::Vector2,
+    InputHandler, Style,
 };
 
 use miniquad_text_rusttype::FontAtlas;
-use std::{collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 mod cursor;
 mod input;
@@ -51,7 +57,7 @@ impl Window {
         margin: f32,
         movable: bool,
         force_focus: bool,
-        font_atlas: Rc<FontAtlas>,
+        fonts: Rc<RefCell<Fonts>>,
     ) -> Window {
         Window {
             id,
@@ -63,7 +69,7 @@ impl Window {
             visible: true,
             was_active: false,
             active: false,
-            draw_commands: CommandsList::new(font_atlas),
+            draw_commands: CommandsList::new(fonts),
             cursor: Cursor::new(
                 Rect::new(
                     position.x,
@@ -145,9 +151,9 @@ pub struct Ui {
 
     moving: Option<(Id, Vector2)>,
     windows: HashMap<Id, Window>,
-    // special window that is always rendered on top of anything
-    // TODO: maybe make modal windows stack instead
-    modal: Option<Window>,
+    // special windows that are always rendered on top of anything,
+    // one for each level of nesting: a combobox inside a popup gets the second one
+    modal: Vec<Window>,
     // another special window
     // always rendered behind everything and do not have borders or scrolls
     // helps using window-less uis
@@ -162,15 +168,25 @@ pub struct Ui {
     drag_hovered_previous_frame: Option<Id>,
     active_window: Option<Id>,
     hovered_window: Id,
-    in_modal: bool,
+    // number of modals begun and not ended yet
+    modal_depth: usize,
     child_window_stack: Vec<Id>,
 
     last_item_clicked: bool,
     last_item_hovered: bool,
 
+    /// Texture with the glyphs rasterized so far, updated in `render`; check `font_atlas_revision`
     pub font_atlas: Rc<FontAtlas>,
-
-    clipboard_selection: String,
+    fonts: Rc<RefCell<Fonts>>,
+    /// Physical pixels per UI pixel
+    scale: f32,
+    /// Set by `set_scale`, applied in `new_frame` to keep the atlas of the recorded commands
+    next_scale: Option<f32>,
+    /// Added by `add_fallback_font`, applied in `new_frame` for the same reason
+    next_fallbacks: Vec<FontId>,
+
+    /// Copied by Ctrl+C and Ctrl+X, `None` keeps the clipboard as it is
+    clipboard_selection: Option<String>,
     clipboard: Box<dyn crate::ClipboardObject>,
 
     key_repeat: key_repeat::KeyRepeat,
@@ -211,7 +227,7 @@ pub(crate) struct WindowContext<'a> {
     pub storage_any: &'a mut AnyStorage,
     pub global_style: &'a Style,
     pub input: &'a mut Input,
-    pub clipboard_selection: &'a mut String,
+    pub clipboard_selection: &'a mut Option<String>,
     pub clipboard: &'a mut dyn crate::ClipboardObject,
     pub focused: bool,
     pub last_item_clicked: &'a mut bool,
@@ -250,6 +266,25 @@ impl<'a> WindowContext<'a> {
         self.window.cursor.scroll.update();
     }
 
+    /// Shadow under the whole window, drawn to the window's background layer
+    pub(crate) fn draw_shadow(&mut self) {
+        let style = self.global_style;
+        if style.window_shadow_size <= 0. {
+            return;
+        }
+
+        let rect = self.window.full_rect();
+        let draw_commands = &mut self.window.draw_commands;
+        let previous_layer = draw_commands.set_layer(DrawLayer::Background);
+        draw_commands.draw_shadow(
+            rect,
+            style.window_shadow_size,
+            style.window_shadow_offset,
+            style.window_shadow(self.focused),
+        );
+        draw_commands.set_layer(previous_layer);
+    }
+
     pub(crate) fn close(&mut self) {
         self.window.want_close = true;
     }
@@ -315,13 +350,13 @@ impl<'a> WindowContext<'a> {
 
 impl InputHandler for Ui {
     fn mouse_down(&mut self, position: (f32, f32)) {
-        let position = Vector2::new(position.0, position.1);
+        let position = Vector2::new(position.0 / self.scale, position.1 / self.scale);
 
         self.input.is_mouse_down = true;
         self.input.click_down = true;
         self.input.mouse_position = position;
 
-        if let Some(ref window) = self.modal {
+        for window in &self.modal {
             let rect = Rect::new(
                 window.position.x,
                 window.position.y,
@@ -366,7 +401,7 @@ impl InputHandler for Ui {
     }
 
     fn mouse_move(&mut self, position: (f32, f32)) {
-        let position = Vector2::new(position.0, position.1);
+        let position = Vector2::new(position.0 / self.scale, position.1 / self.scale);
 
         // assuming that the click was to the root window
         // if it is not - hovered_window will be setted a little later in that function
@@ -380,13 +415,11 @@ impl InputHandler for Ui {
             }
         }
 
-        match &self.modal {
-            Some(modal) if modal.was_active || modal.active => {
-                if modal.full_rect().contains(position) {
-                    self.hovered_window = modal.id;
-                }
+        // the nested modals are on top
+        for modal in &self.modal {
+            if (modal.was_active || modal.active) && modal.full_rect().contains(position) {
+                self.hovered_window = modal.id;
             }
-            _ => {}
         }
 
         self.input.mouse_position = position;
@@ -418,7 +451,9 @@ impl InputHandler for Ui {
         }
 
         if ctrl && (key == KeyCode::C || key == KeyCode::X) {
-            self.clipboard.set(&self.clipboard_selection);
+            if let Some(selection) = &self.clipboard_selection {
+                self.clipboard.set(selection);
+            }
         }
 
         if key != KeyCode::Control && self.key_repeat.add_repeat_gap(key, self.time) {
@@ -433,30 +468,18 @@ impl InputHandler for Ui {
 
 impl Ui {
     pub fn new() -> Ui {
-        let mut font_atlas = FontAtlas::new(
-            &include_bytes!("../assets/ProggyClean.ttf")[..],
-            13,
-            FontAtlas::ascii_character_list(),
-        )
-        .unwrap();
-
-        let white_square = [(0, 0), (1, 0), (1, 1), (0, 1)];
-        let w = font_atlas.texture.width;
-        for pixel in white_square.iter() {
-            font_atlas.texture.data[(pixel.0 + w * pixel.1 + 0) as usize] = 255;
-            font_atlas.texture.data[(pixel.0 + w * pixel.1 + 1) as usize] = 255;
-            font_atlas.texture.data[(pixel.0 + w * pixel.1 + 2) as usize] = 255;
-            font_atlas.texture.data[(pixel.0 + w * pixel.1 + 3) as usize] = 255;
-        }
+        let mut fonts = Fonts::new();
+        let mut font_atlas = font::empty_atlas();
+        fonts.update_atlas(&mut font_atlas);
+        let fonts = Rc::new(RefCell::new(fonts));
 
-        let font_atlas = Rc::new(font_atlas);
         Ui {
             input: Input::default(),
             style: Style::default(),
             frame: 0,
             moving: None,
             windows: HashMap::default(),
-            modal: None,
+            modal: vec![],
             root_window: {
                 let mut window = Window::new(
                     0,
@@ -472,7 +495,7 @@ impl Ui {
                     0.0,
                     false,
                     true,
-                    font_atlas.clone(),
+                    fonts.clone(),
                 );
                 window.active = true;
                 window.was_active = true;
@@ -482,14 +505,18 @@ impl Ui {
             dragging: None,
             active_window: None,
             hovered_window: 0,
-            in_modal: false,
+            modal_depth: 0,
             child_window_stack: vec![],
             drag_hovered: None,
             drag_hovered_previous_frame: None,
             storage_u32: HashMap::default(),
             storage_any: AnyStorage::default(),
             font_atlas,
-            clipboard_selection: String::new(),
+            fonts,
+            scale: 1.,
+            next_scale: None,
+            next_fallbacks: vec![],
+            clipboard_selection: None,
             clipboard: Box::new(crate::clipboard::LocalClipboard::new()),
             time: 0.0,
             key_repeat: key_repeat::KeyRepeat::new(),
@@ -502,6 +529,108 @@ impl Ui {
         self.style = style;
     }
 
+    /// Size of a UI pixel in the screen pixels, 2.0 for a typical HiDPI display.
+    ///
+    /// Widgets, `Style` metrics and positions given to the `Ui` stay in UI pixels,
+    /// `render` scales everything to the screen pixels and input events are expected
+    /// in the screen pixels. Fonts are rasterized again with the scaled size.
+    ///
+    /// The new scale takes effect on the next `new_frame`, the current frame is rendered
+    /// with the old one.
+    pub fn set_scale(&mut self, scale: f32) {
+        assert!(scale > 0., "UI scale should be positive");
+
+        self.next_scale = Some(scale);
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Load TTF font data to be used with the given pixel size.
+    /// Glyphs are rasterized into `font_atlas` on the first use.
+    pub fn load_font(&mut self, bytes: &[u8], size: u32) -> Result<FontId, FontError> {
+        self.fonts.borrow_mut().load(bytes, size)
+    }
+
+    /// Glyphs missing in the active font will be taken from this font, with the active font's size.
+    /// Fallbacks are tried in the order they were added.
+    ///
+    /// The glyphs are rasterized again with the fallback, so it takes effect on the next
+    /// `new_frame`, the current frame is rendered without it.
+    pub fn add_fallback_font(&mut self, font: FontId) {
+        self.next_fallbacks.push(font);
+    }
+
+    /// Changes each time `render` updates `font_atlas` with new glyphs.
+    /// The backend should upload the texture again when this differs from the last uploaded one.
+    pub fn font_atlas_revision(&self) -> u64 {
+        self.fonts.borrow().revision()
+    }
+
+    /// Built-in ProggyClean, 13 pixels
+    pub fn default_font(&self) -> FontId {
+        self.fonts.borrow().default_font()
+    }
+
+    /// Use the font for all the following text until the matching `pop_font`
+    pub fn push_font(&mut self, font: FontId) {
+        self.fonts.borrow_mut().push(font);
+    }
+
+    pub fn pop_font(&mut self) {
+        self.fonts.borrow_mut().pop();
+    }
+
+    /// Size, lines and caret positions of the text drawn with the active font,
+    /// laid out the same way `label` draws it.
+    pub fn measure_text(&self, text: &str) -> TextMetrics {
+        let mut fonts = self.fonts.borrow_mut();
+        let (line_height, baseline) = (fonts.line_height(), fonts.baseline());
+
+        text::measure(text, line_height, baseline, |character| {
+            fonts.advance(character)
+        })
+    }
+
+    /// Show the text in a box next to the mouse cursor, above all the windows.
+    /// Should be called on each frame the tooltip should be visible,
+    /// usually while some widget is hovered.
+    pub fn tooltip(&mut self, text: &str) {
+        let style = &self.style;
+        let position = self.input.mouse_position + Vector2::new(12., 16.);
+        let margin = style.margin * 2.;
+
+        let draw_commands = &mut self.root_window.draw_commands;
+        let previous_layer = draw_commands.set_layer(DrawLayer::Foreground);
+
+        let lines = draw_commands.wrap_text(text, style.tooltip_max_width);
+        let line_height = draw_commands.line_height();
+        let width = lines.iter().fold(0.0f32, |width, line| {
+            width.max(draw_commands.label_size(line, None).x)
+        });
+
+        draw_commands.draw_rect(
+            Rect::new(
+                position.x,
+                position.y,
+                width + margin * 2.,
+                line_height * lines.len() as f32 + margin * 2.,
+            ),
+            style.tooltip_border,
+            style.tooltip_background,
+        );
+        for (n, line) in lines.iter().enumerate() {
+            draw_commands.draw_label(
+                line,
+                position + Vector2::new(margin, margin + n as f32 * line_height),
+                style.text(true),
+            );
+        }
+
+        draw_commands.set_layer(previous_layer);
+    }
+
     pub(crate) fn begin_window(
         &mut self,
         id: Id,
@@ -521,7 +650,7 @@ impl Ui {
 
         let focused = self.is_focused(id);
         let margin = self.style.margin;
-        let font_atlas = self.font_atlas.clone();
+        let fonts = self.fonts.clone();
         let windows_focus_order = &mut self.windows_focus_order;
 
         let parent_force_focus = match parent {
@@ -534,12 +663,13 @@ impl Ui {
                 .map_or(false, |window| window.force_focus),
             _ => false,
         };
-        let parent_clip_rect = if let Some(parent) = parent {
-            self.windows
+        let parent_clip_rect = match parent {
+            Some(0) => self.root_window.draw_commands.clipping_zone,
+            Some(parent) => self
+                .windows
                 .get(&parent)
-                .and_then(|window| window.draw_commands.clipping_zone)
-        } else {
-            None
+                .and_then(|window| window.draw_commands.clipping_zone),
+            None => None,
         };
 
         let window = &mut *self.windows.entry(id).or_insert_with(|| {
@@ -556,14 +686,14 @@ impl Ui {
                 margin,
                 movable,
                 parent_force_focus,
-                font_atlas,
+                fonts,
             )
         });
 
         window.size = size;
         window.want_close = false;
         window.active = true;
-        window.draw_commands.clipping_zone = parent_clip_rect;
+        window.draw_commands.set_base_clip(parent_clip_rect);
 
         // top level windows are moveble, so we update their position only on the first frame
         // while the child windows are not moveble and should update their position each frame
@@ -595,23 +725,25 @@ impl Ui {
         size: Vector2,
     ) -> WindowContext {
         self.input.window_active = true;
-        self.in_modal = true;
 
-        let font_atlas = self.font_atlas.clone();
+        let fonts = self.fonts.clone();
 
-        let window = self.modal.get_or_insert_with(|| {
-            Window::new(id, None, position, size, 0.0, 0.0, false, true, font_atlas)
-        });
+        if self.modal.len() == self.modal_depth {
+            self.modal.push(Window::new(
+                id, None, position, size, 0.0, 0.0, false, true, fonts,
+            ));
+        }
+        let window = &mut self.modal[self.modal_depth];
+        self.modal_depth += 1;
 
         window.parent = self.active_window;
         window.size = size;
         window.want_close = false;
         window.active = true;
-        window.draw_commands.clipping_zone =
-            Some(Rect::new(position.x, position.y, size.x, size.y));
+        window.draw_commands.set_base_clip(None);
         window.set_position(position);
 
-        WindowContext {
+        let mut context = WindowContext {
             focused: true,
             window,
             input: &mut self.input,
@@ -625,11 +757,20 @@ impl Ui {
             clipboard: &mut *self.clipboard,
             last_item_clicked: &mut self.last_item_clicked,
             last_item_hovered: &mut self.last_item_hovered,
-        }
+        };
+
+        // shadow should go outside of the modal, so it is drawn before clipping
+        context.draw_shadow();
+        context
+            .window
+            .draw_commands
+            .set_base_clip(Some(Rect::new(position.x, position.y, size.x, size.y)));
+
+        context
     }
 
     pub(crate) fn end_modal(&mut self) {
-        self.in_modal = false;
+        self.modal_depth -= 1;
         self.input.window_active = self.is_input_hovered(self.active_window.unwrap_or(0));
     }
 
@@ -640,7 +781,7 @@ impl Ui {
 
     pub(crate) fn get_active_window_context(&mut self) -> WindowContext {
         let focused;
-        let window = if self.in_modal == false {
+        let window = if self.modal_depth == 0 {
             match self.active_window {
                 None | Some(0) => {
                     focused = true;
@@ -653,7 +794,7 @@ impl Ui {
             }
         } else {
             focused = true;
-            self.modal.as_mut().unwrap()
+            &mut self.modal[self.modal_depth - 1]
         };
 
         WindowContext {
@@ -673,6 +814,24 @@ impl Ui {
         }
     }
 
+    fn root_window_context(&mut self) -> WindowContext<'_> {
+        WindowContext {
+            window: &mut self.root_window,
+            focused: true,
+            input: &mut self.input,
+            global_style: &self.style,
+            dragging: &mut self.dragging,
+            drag_hovered: &mut self.drag_hovered,
+            drag_hovered_previous_frame: &mut self.drag_hovered_previous_frame,
+            storage_u32: &mut self.storage_u32,
+            storage_any: &mut self.storage_any,
+            clipboard_selection: &mut self.clipboard_selection,
+            clipboard: &mut *self.clipboard,
+            last_item_clicked: &mut self.last_item_clicked,
+            last_item_hovered: &mut self.last_item_hovered,
+        }
+    }
+
     /// Returns true if the last widget which had `.ui` called on it is being clicked.
     pub fn last_item_clicked(&mut self) -> bool {
         self.last_item_clicked
@@ -733,7 +892,11 @@ impl Ui {
         self.input.cursor_grabbed
     }
 
+    /// `mouse_position` is in the screen pixels, like the input events
     pub fn is_mouse_over(&self, mouse_position: Vector2) -> bool {
+        let mouse_position =
+            Vector2::new(mouse_position.x / self.scale, mouse_position.y / self.scale);
+
         for window in self.windows_focus_order.iter() {
             let window = &self.windows[window];
             if window.was_active == false {
@@ -776,7 +939,7 @@ impl Ui {
         // window id
         // so need to figure the root id
 
-        if self.in_modal {
+        if self.modal_depth != 0 {
             return true;
         } else {
             self.child_window_stack
@@ -823,6 +986,14 @@ impl Ui {
         self.input.window_active = self.hovered_window == 0;
 
         self.key_repeat.new_frame(self.time);
+        self.fonts.borrow_mut().reset_stack();
+        if let Some(scale) = self.next_scale.take() {
+            self.scale = scale;
+            self.fonts.borrow_mut().set_scale(scale);
+        }
+        for font in self.next_fallbacks.drain(..) {
+            self.fonts.borrow_mut().add_fallback(font);
+        }
 
         for (_, window) in &mut self.windows {
             window.draw_commands.clear();
@@ -848,7 +1019,15 @@ impl Ui {
     }
 
     pub fn render(&mut self, draw_list: &mut Vec<DrawList>) {
-        self.render_window(&self.root_window, Vector2::new(0., 0.), draw_list);
+        self.fonts.borrow_mut().update_atlas(&mut self.font_atlas);
+
+        let root = &self.root_window;
+
+        // root window layers are global: background is below all the windows,
+        // foreground is above everything, including modals and dragged groups
+        self.render_layer(root, DrawLayer::Background, Vector2::new(0., 0.), draw_list);
+        self.render_layer(root, DrawLayer::Content, Vector2::new(0., 0.), draw_list);
+        self.render_childs(root, Vector2::new(0., 0.), draw_list);
 
         for window in self.windows_focus_order.iter().rev() {
             let window = &self.windows[window];
@@ -857,7 +1036,7 @@ impl Ui {
             }
         }
 
-        if let Some(modal) = self.modal.as_ref() {
+        for modal in &self.modal {
             if modal.was_active {
                 self.render_window(modal, Vector2::new(0., 0.), draw_list);
             }
@@ -868,13 +1047,38 @@ impl Ui {
 
             self.render_window(window, self.input.mouse_position - orig, draw_list);
         }
+
+        self.render_layer(root, DrawLayer::Foreground, Vector2::new(0., 0.), draw_list);
     }
 
     fn render_window(&self, window: &Window, offset: Vector2, draw_list: &mut Vec<DrawList>) {
-        for cmd in &window.draw_commands.commands {
-            crate::draw_list::render_command(draw_list, cmd.offset(offset));
+        self.render_layer(window, DrawLayer::Background, offset, draw_list);
+        self.render_layer(window, DrawLayer::Content, offset, draw_list);
+        self.render_childs(window, offset, draw_list);
+        self.render_layer(window, DrawLayer::Foreground, offset, draw_list);
+    }
+
+    fn render_layer(
+        &self,
+        window: &Window,
+        layer: DrawLayer,
+        offset: Vector2,
+        draw_list: &mut Vec<DrawList>,
+    ) {
+        let font_texture = &self.font_atlas.texture;
+        let font_texture_size = Vector2::new(font_texture.width as f32, font_texture.height as f32);
+
+        for cmd in window.draw_commands.commands(layer) {
+            crate::draw_list::render_command(
+                draw_list,
+                cmd.offset(offset).scale(self.scale),
+                font_texture_size,
+                self.scale,
+            );
         }
+    }
 
+    fn render_childs(&self, window: &Window, offset: Vector2, draw_list: &mut Vec<DrawList>) {
         for child in &window.childs {
             let child_window = &self.windows[child];
             if window.content_rect().overlaps(&child_window.full_rect()) {
@@ -904,7 +1108,19 @@ impl Ui {
     pub fn canvas(&mut self) -> DrawCanvas {
         let context = self.get_active_window_context();
 
-        DrawCanvas { context }
+        DrawCanvas {
+            context,
+            layer: DrawLayer::Content,
+        }
+    }
+
+    /// Canvas over the whole screen, not bound to any window.
+    /// With `DrawLayer::Background` it draws behind all the windows,
+    /// with `DrawLayer::Foreground` - above all of them.
+    pub fn global_canvas(&mut self, layer: DrawLayer) -> DrawCanvas<'_> {
+        let context = self.root_window_context();
+
+        DrawCanvas { context, layer }
     }
 
     /// small hack to keep some internal state
@@ -7,6 +7,7 @@ mod group;
 mod input;
 mod label;
 mod popup;
+mod rich_text;
 mod separator;
 mod slider;
 mod tabbar;
@@ -17,12 +18,15 @@ mod window;
 pub use button::Button;
 pub use checkbox::Checkbox;
 pub use combobox::ComboBox;
-pub use editbox::Editbox;
+pub use editbox::{
+    Completer, Editbox, EditboxHistory, EditboxResponse, Highlighter, SyntaxHighlighter, TextEdit,
+};
 pub use group::{Group, GroupToken};
 #[allow(deprecated)]
 pub use input::{InputText, InputField};
 pub use label::Label;
 pub use popup::Popup;
+pub use rich_text::{RichText, Span};
 pub use slider::Slider;
 pub use tabbar::Tabbar;
 pub use texture::Texture;
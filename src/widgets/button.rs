@@ -1,10 +1,12 @@
-use crate::{types::Vector2, Layout, Rect, Ui};
+use crate::{types::Vector2, Aligment, Layout, Rect, Ui, VerticalAligment};
 
 use std::borrow::Cow;
 
 pub struct Button<'a> {
     position: Option<Vector2>,
     size: Option<Vector2>,
+    aligment: Aligment,
+    vertical_aligment: VerticalAligment,
     label: Cow<'a, str>,
 }
 
@@ -16,6 +18,8 @@ impl<'a> Button<'a> {
         Button {
             position: None,
             size: None,
+            aligment: Aligment::Left,
+            vertical_aligment: VerticalAligment::Top,
             label: label.into(),
         }
     }
@@ -33,6 +37,19 @@ impl<'a> Button<'a> {
         }
     }
 
+    /// Label placement inside a button with explicit `size`.
+    /// Labels not fitting the size are truncated and shown in a tooltip on hover.
+    pub fn aligment(self, aligment: Aligment) -> Self {
+        Button { aligment, ..self }
+    }
+
+    pub fn vertical_aligment(self, vertical_aligment: VerticalAligment) -> Self {
+        Button {
+            vertical_aligment,
+            ..self
+        }
+    }
+
     pub fn ui(self, ui: &mut Ui) -> bool {
         let mut context = ui.get_active_window_context();
 
@@ -60,15 +77,24 @@ impl<'a> Button<'a> {
                 hovered && context.input.is_mouse_down,
             ),
         );
-        context.window.draw_commands.draw_label(
+        let margin = context.global_style.margin_button;
+        let truncated = context.window.draw_commands.draw_label_aligned(
             &self.label,
-            pos + Vector2::new(
-                context.global_style.margin_button,
-                context.global_style.margin_button,
+            Rect::new(
+                pos.x + margin,
+                pos.y + margin,
+                size.x - margin * 2.,
+                size.y - margin,
             ),
-            Some(context.global_style.text(context.focused)),
+            self.aligment,
+            self.vertical_aligment,
+            context.global_style.text(context.focused),
         );
 
+        if hovered && truncated {
+            ui.tooltip(&self.label);
+        }
+
         clicked
     }
 }
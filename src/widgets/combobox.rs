@@ -2,13 +2,15 @@ use crate::Color;
 use crate::{
     hash,
     types::{Rect, Vector2},
-    Id, Layout, Ui,
+    Aligment, Id, Layout, Ui, VerticalAligment,
 };
 
 pub struct ComboBox<'a, 'b, 'c> {
     id: Id,
     label: &'a str,
     variants: &'b [&'c str],
+    aligment: Aligment,
+    vertical_aligment: VerticalAligment,
 }
 
 impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
@@ -17,6 +19,8 @@ impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
             id,
             label: "",
             variants,
+            aligment: Aligment::Left,
+            vertical_aligment: VerticalAligment::Top,
         }
     }
 
@@ -24,10 +28,25 @@ impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
         ComboBox {
             id: self.id,
             variants: self.variants,
+            aligment: self.aligment,
+            vertical_aligment: self.vertical_aligment,
             label,
         }
     }
 
+    /// Alignment of the selected variant and of the variants in the list.
+    /// Texts not fitting are truncated and shown in a tooltip on hover.
+    pub fn aligment(self, aligment: Aligment) -> Self {
+        ComboBox { aligment, ..self }
+    }
+
+    pub fn vertical_aligment(self, vertical_aligment: VerticalAligment) -> Self {
+        ComboBox {
+            vertical_aligment,
+            ..self
+        }
+    }
+
     pub fn ui(self, ui: &mut Ui, data: &mut usize) -> usize {
         let mut context = ui.get_active_window_context();
 
@@ -58,9 +77,16 @@ impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
             context.global_style.editbox_background(context.focused),
             None,
         );
-        context.window.draw_commands.draw_label(
+        let selected_truncated = context.window.draw_commands.draw_label_aligned(
             self.variants[*data],
-            Vector2::new(pos.x + 5., pos.y + 2.),
+            Rect::new(
+                pos.x + 5.,
+                pos.y + 2.,
+                active_area_w - triangle_area_w - 5.,
+                size.y - 2.,
+            ),
+            self.aligment,
+            self.vertical_aligment,
             Color::from_rgba(0, 0, 0, 255),
         );
 
@@ -81,11 +107,21 @@ impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
             Color::new(0.7, 0.7, 0.7, 1.0),
         );
 
-        context.window.draw_commands.draw_label(
+        let label_rect = Rect::new(
+            pos.x + size.x / 2. + 5.,
+            pos.y + 2.,
+            size.x / 2. - 5.,
+            size.y - 2.,
+        );
+        let label_truncated = context.window.draw_commands.draw_label_aligned(
             self.label,
-            Vector2::new(pos.x + size.x / 2. + 5., pos.y + 2.),
+            label_rect,
+            Aligment::Left,
+            VerticalAligment::Top,
             Color::from_rgba(0, 0, 0, 255),
         );
+        let label_hovered =
+            context.input.window_active && label_rect.contains(context.input.mouse_position);
 
         let modal_size = Vector2::new(200.0, self.variants.len() as f32 * 20.0);
         let modal_rect = Rect::new(pos.x, pos.y + 20.0, modal_size.x, modal_size.y);
@@ -112,6 +148,7 @@ impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
                 .storage_any
                 .get_or_default::<bool>(hash!(self.id, "combobox_state"));
 
+            let mut truncated_hovered = None;
             for (i, variant) in self.variants.iter().enumerate() {
                 let rect = Rect::new(
                     pos.x + 5.0,
@@ -131,11 +168,16 @@ impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
                         .combobox_variant_background(hovered, *data == i),
                 );
 
-                context.window.draw_commands.draw_label(
+                let truncated = context.window.draw_commands.draw_label_aligned(
                     variant,
-                    Vector2::new(pos.x + 7., pos.y + i as f32 * 20.0 + 20.0 + 2.0),
+                    Rect::new(rect.x + 2., rect.y + 2., rect.w - 4., rect.h - 2.),
+                    self.aligment,
+                    self.vertical_aligment,
                     Color::from_rgba(0, 0, 0, 255),
                 );
+                if hovered && truncated {
+                    truncated_hovered = Some(*variant);
+                }
 
                 if hovered && context.input.click_up {
                     *data = i;
@@ -143,6 +185,17 @@ impl<'a, 'b, 'c> ComboBox<'a, 'b, 'c> {
                 }
             }
             ui.end_modal();
+
+            if let Some(variant) = truncated_hovered {
+                ui.tooltip(variant);
+            }
+        }
+
+        if hovered && selected_truncated {
+            ui.tooltip(self.variants[*data]);
+        }
+        if label_hovered && label_truncated {
+            ui.tooltip(self.label);
         }
 
         *data
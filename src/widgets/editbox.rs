@@ -1,25 +1,125 @@
 use crate::{
-    hash,
+    draw_command::CommandsList,
+    hash, text,
     types::{Rect, Vector2},
-    ui::{InputCharacter, Key, KeyCode},
-    Id, Layout, Ui,
+    ui::{InputCharacter, Key, KeyCode, WindowContext},
+    Id, Layout, Style, Ui,
 };
 
 pub struct Editbox<'a> {
     id: Id,
     size: Vector2,
     multiline: bool,
+    wrap: bool,
+    line_numbers: bool,
+    match_brackets: bool,
+    password: bool,
     select_all: bool,
     filter: Option<&'a dyn Fn(char) -> bool>,
+    highlighter: Option<&'a dyn Highlighter>,
+    completer: Option<&'a dyn Completer>,
+    placeholder: Option<&'a str>,
+    /// In characters
+    max_length: Option<usize>,
+    undo_limit: usize,
     pos: Option<Vector2>,
     line_height: f32,
 }
 
+mod completion;
+mod highlighter;
 mod text_editor;
 
-use text_editor::EditboxState;
+pub use completion::Completer;
+pub use highlighter::{Highlighter, SyntaxHighlighter};
+pub use text_editor::{EditboxHistory, TextEdit};
+
+use text_editor::{find_matches, Completion, EditboxState, Find};
 
 const LEFT_MARGIN: f32 = 2.;
+const PADDING: f32 = 5.;
+const CURSOR_WIDTH: f32 = 2.;
+const GUTTER_PADDING: f32 = 6.;
+/// Spaces inserted with Tab
+const TAB_WIDTH: usize = 4;
+const FIND_BAR_PADDING: f32 = 3.;
+const UNDO_LIMIT: usize = 100;
+/// Rows of the completion popup, it scrolls to keep the selected one visible
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Something done with the find bar, by a key or by a click
+#[derive(Clone, Copy)]
+enum FindAction {
+    EditQuery,
+    EditReplacement,
+    /// Select the first occurrence at the current one or after it, while the query is typed
+    Search,
+    Previous,
+    Next,
+    Replace,
+    ReplaceAll,
+}
+
+/// A place for the cursor: before the character at `index`, or after the end of the text
+struct Caret {
+    index: usize,
+    character: char,
+    x: f32,
+    advance: f32,
+}
+
+/// The text split into the visual lines.
+/// Each line keeps the spaces or the newline it was broken at,
+/// the last one also has the caret after the end of the text.
+struct TextLayout {
+    lines: Vec<Vec<Caret>>,
+    /// Width of the line numbers on the left of the text
+    gutter: f32,
+}
+
+impl TextLayout {
+    /// Line and x of the caret before the byte `index`
+    fn find(&self, index: usize) -> (usize, f32) {
+        for (n, line) in self.lines.iter().enumerate() {
+            if let Some(caret) = line.iter().find(|caret| caret.index == index) {
+                return (n, caret.x);
+            }
+        }
+        (self.lines.len() - 1, 0.)
+    }
+
+    /// Byte index of the caret on the line closest to `x`
+    fn index_at(&self, line: usize, x: f32) -> usize {
+        let line = &self.lines[line];
+
+        line.iter()
+            .find(|caret| x < caret.x + caret.advance / 2.)
+            .unwrap_or_else(|| line.last().unwrap())
+            .index
+    }
+
+    /// Position after the last character of the longest line
+    fn width(&self) -> f32 {
+        self.lines
+            .iter()
+            .filter_map(|line| line.last())
+            .map(|caret| caret.x)
+            .fold(0., f32::max)
+    }
+}
+
+/// What happened to the editbox during the frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EditboxResponse {
+    /// The text was changed by the user
+    pub edited: bool,
+    /// Enter was pressed, Ctrl+Enter for the multiline editboxes
+    pub submitted: bool,
+    /// Escape was pressed, the editbox lost the input focus
+    pub cancelled: bool,
+    pub gained_focus: bool,
+    pub lost_focus: bool,
+}
 
 impl<'a> Editbox<'a> {
     pub fn new(id: Id, size: Vector2) -> Editbox<'a> {
@@ -27,8 +127,17 @@ impl<'a> Editbox<'a> {
             id,
             size,
             filter: None,
+            highlighter: None,
+            completer: None,
+            placeholder: None,
+            max_length: None,
+            undo_limit: UNDO_LIMIT,
             select_all: false,
             multiline: true,
+            wrap: true,
+            line_numbers: false,
+            match_brackets: false,
+            password: false,
             pos: None,
             line_height: 14.0,
         }
@@ -38,6 +147,34 @@ impl<'a> Editbox<'a> {
         Editbox { multiline, ..self }
     }
 
+    /// Break the long lines of the multiline editbox at the editbox width, on by default.
+    /// Without wrapping the text scrolls horizontally to keep the cursor visible.
+    pub fn wrap(self, wrap: bool) -> Self {
+        Editbox { wrap, ..self }
+    }
+
+    /// Show the numbers of the lines in a gutter on the left of the multiline editbox
+    pub fn line_numbers(self, line_numbers: bool) -> Self {
+        Editbox {
+            line_numbers,
+            ..self
+        }
+    }
+
+    /// Frame the bracket next to the cursor together with its pair
+    pub fn match_brackets(self, match_brackets: bool) -> Self {
+        Editbox {
+            match_brackets,
+            ..self
+        }
+    }
+
+    /// Draw each character as a mask, the selection can't be copied or cut
+    /// and there is no find bar to show it in
+    pub fn password(self, password: bool) -> Self {
+        Editbox { password, ..self }
+    }
+
     pub fn select_all(self) -> Self {
         Editbox {
             select_all: true,
@@ -59,16 +196,533 @@ impl<'a> Editbox<'a> {
         }
     }
 
-    pub fn filter<'b>(self, filter: &'b dyn Fn(char) -> bool) -> Editbox<'b> {
+    /// Hint shown while the text is empty
+    pub fn placeholder(self, placeholder: &'a str) -> Self {
+        Editbox {
+            placeholder: Some(placeholder),
+            ..self
+        }
+    }
+
+    /// Maximum number of characters that can be typed or pasted.
+    /// Longer text given to the editbox is kept as is.
+    pub fn max_length(self, max_length: usize) -> Self {
+        Editbox {
+            max_length: Some(max_length),
+            ..self
+        }
+    }
+
+    /// Number of undo steps kept, 100 by default.
+    /// Typing, Backspace and Delete make a step about a word long, a paste is a step of its own.
+    pub fn undo_limit(self, undo_limit: usize) -> Self {
+        Editbox { undo_limit, ..self }
+    }
+
+    pub fn filter<'b>(self, filter: &'b dyn Fn(char) -> bool) -> Editbox<'b>
+    where
+        'a: 'b,
+    {
         Editbox {
             id: self.id,
+            placeholder: self.placeholder,
+            max_length: self.max_length,
+            undo_limit: self.undo_limit,
             line_height: self.line_height,
             pos: self.pos,
             multiline: self.multiline,
+            wrap: self.wrap,
+            line_numbers: self.line_numbers,
+            match_brackets: self.match_brackets,
+            password: self.password,
             select_all: self.select_all,
             size: self.size,
             filter: Some(filter),
+            highlighter: self.highlighter,
+            completer: self.completer,
+        }
+    }
+
+    /// Color the text with the highlighter, not used in the password mode
+    pub fn highlighter(self, highlighter: &'a dyn Highlighter) -> Self {
+        Editbox {
+            highlighter: Some(highlighter),
+            ..self
+        }
+    }
+
+    /// Suggest the words to complete the one before the cursor in a popup.
+    /// Up and Down choose the suggestion, Tab or Enter accept it and Escape closes the popup.
+    pub fn completer(self, completer: &'a dyn Completer) -> Self {
+        Editbox {
+            completer: Some(completer),
+            ..self
+        }
+    }
+
+    /// Width available for the text. Multiline editboxes always leave room for the scroll bar,
+    /// so the wrapping does not change when it shows up.
+    fn text_width(&self, style: &Style, gutter: f32) -> f32 {
+        let scroll_bar = if self.multiline {
+            style.scroll_width
+        } else {
+            0.
+        };
+
+        self.size.x - 2. * PADDING - LEFT_MARGIN - CURSOR_WIDTH - scroll_bar - gutter
+    }
+
+    fn layout(&self, text: &str, draw_commands: &CommandsList, style: &Style) -> TextLayout {
+        let mask = if draw_commands.has_glyph('•') {
+            '•'
+        } else {
+            '*'
+        };
+        let advance = |character: char| match character {
+            '\n' => 1.5, // hack to make cursor on newlines visible
+            _ if self.password => draw_commands.character_advance(mask),
+            _ => draw_commands.character_advance(character),
+        };
+
+        let gutter = if self.multiline && self.line_numbers {
+            let digits = (text.matches('\n').count() + 1).to_string().len();
+
+            digits as f32 * draw_commands.character_advance('0') + GUTTER_PADDING
+        } else {
+            0.
+        };
+
+        let starts: Vec<usize> = if self.multiline && self.wrap {
+            text::wrap(text, self.text_width(style, gutter), &advance)
+                .iter()
+                .map(|line| line.as_ptr() as usize - text.as_ptr() as usize)
+                .collect()
+        } else if self.multiline {
+            std::iter::once(0)
+                .chain(text.match_indices('\n').map(|(n, _)| n + 1))
+                .collect()
+        } else {
+            vec![0]
+        };
+        let ends = starts
+            .iter()
+            .skip(1)
+            .copied()
+            .chain(std::iter::once(text.len()));
+
+        let lines = starts
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| {
+                let mut x = LEFT_MARGIN;
+                let end_of_text = if end == text.len() {
+                    Some((text.len(), ' '))
+                } else {
+                    None
+                };
+
+                text[start..end]
+                    .char_indices()
+                    .map(|(n, character)| (start + n, character))
+                    .chain(end_of_text)
+                    .map(|(index, character)| {
+                        let caret = Caret {
+                            index,
+                            character,
+                            x,
+                            advance: advance(character),
+                        };
+                        x += caret.advance;
+                        caret
+                    })
+                    .collect()
+            })
+            .collect();
+
+        TextLayout { lines, gutter }
+    }
+
+    /// How many characters more could be inserted
+    fn room_left(&self, text: &str) -> usize {
+        self.max_length.map_or(usize::MAX, |max_length| {
+            max_length.saturating_sub(text.chars().count())
+        })
+    }
+
+    /// Whether replacing `count` occurrences keeps the text within the maximum length
+    fn room_for_replacing(&self, text: &str, find: &Find, count: usize) -> bool {
+        let growth = find
+            .replacement
+            .chars()
+            .count()
+            .saturating_sub(find.query.chars().count());
+
+        self.room_left(text) >= growth * count
+    }
+
+    fn apply_find_action(
+        &self,
+        action: FindAction,
+        text: &mut String,
+        state: &mut EditboxState,
+        find: &mut Find,
+    ) {
+        match action {
+            FindAction::EditQuery => find.editing_replacement = false,
+            FindAction::EditReplacement => find.editing_replacement = true,
+            FindAction::Search => {
+                let from = state
+                    .selection
+                    .map_or(state.cursor, |(start, end)| start.min(end));
+                state.find_from(text, &find.query, from);
+            }
+            FindAction::Previous => state.find_previous(text, &find.query),
+            FindAction::Next => state.find_next(text, &find.query),
+            FindAction::Replace => {
+                if self.room_for_replacing(text, find, 1) {
+                    state.replace(text, &find.query, &find.replacement);
+                }
+            }
+            FindAction::ReplaceAll => {
+                let count = find_matches(text, &find.query).len();
+                if self.room_for_replacing(text, find, count) {
+                    state.replace_all(text, &find.query, &find.replacement);
+                }
+            }
+        }
+    }
+
+    /// Suggestions for the word before the cursor, `None` if there is nothing to suggest.
+    /// The word itself is not suggested, so the popup closes once a suggestion is accepted.
+    fn complete(
+        &self,
+        completer: &dyn Completer,
+        text: &str,
+        state: &EditboxState,
+    ) -> Option<Completion> {
+        let word_begin = state.cursor - state.find_word_begin(text, state.cursor);
+        let word = &text[word_begin as usize..state.cursor as usize];
+        if word.is_empty() || state.selection.is_some() {
+            return None;
+        }
+
+        let suggestions: Vec<String> = completer
+            .complete(word)
+            .into_iter()
+            .filter(|suggestion| suggestion != word)
+            .collect();
+        if suggestions.is_empty() {
+            return None;
+        }
+
+        Some(Completion {
+            suggestions,
+            selected: 0,
+            word_begin,
+            rect: Rect::default(),
+        })
+    }
+
+    fn accept_completion(
+        &self,
+        text: &mut String,
+        state: &mut EditboxState,
+        completion: &Completion,
+    ) {
+        let suggestion = &completion.suggestions[completion.selected];
+        let word = &text[completion.word_begin as usize..state.cursor as usize];
+        let growth = suggestion
+            .chars()
+            .count()
+            .saturating_sub(word.chars().count());
+
+        if self.room_left(text) >= growth {
+            state.complete_word(text, completion.word_begin, suggestion);
+        }
+    }
+
+    /// Keys choosing from the open completion popup, false for the ones left to the text
+    fn completion_input(
+        &self,
+        input: &InputCharacter,
+        text: &mut String,
+        state: &mut EditboxState,
+    ) -> bool {
+        use KeyCode::*;
+
+        let completion = match &mut state.completion {
+            Some(completion) => completion,
+            None => return false,
+        };
+        let count = completion.suggestions.len();
+
+        match input.key {
+            Key::KeyCode(Up) => completion.selected = (completion.selected + count - 1) % count,
+            Key::KeyCode(Down) => completion.selected = (completion.selected + 1) % count,
+            Key::KeyCode(Tab) | Key::KeyCode(Enter) => {
+                let completion = state.completion.take().unwrap();
+                self.accept_completion(text, state, &completion);
+            }
+            // closes the popup, the editbox keeps the focus
+            Key::KeyCode(Escape) => state.completion = None,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Draw the suggestions on top of everything, returns true if one was clicked
+    fn draw_completion_popup(&self, ui: &mut Ui, completion: &mut Completion) -> bool {
+        let rect = completion.rect;
+        let row_height = self.line_height + 4.;
+        let context = ui.begin_modal(
+            hash!(self.id, "completion"),
+            Vector2::new(rect.x, rect.y),
+            Vector2::new(rect.w, rect.h),
+        );
+
+        let first = completion.selected.saturating_sub(MAX_SUGGESTIONS - 1);
+        let mut clicked = false;
+        for (n, suggestion) in completion
+            .suggestions
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(MAX_SUGGESTIONS)
+        {
+            let row = Rect::new(
+                rect.x,
+                rect.y + (n - first) as f32 * row_height,
+                rect.w,
+                row_height,
+            );
+            let hovered = row.contains(context.input.mouse_position);
+            let selected = n == completion.selected;
+
+            context.window.draw_commands.draw_rect(
+                row,
+                context
+                    .global_style
+                    .combobox_variant_border(hovered, selected),
+                context
+                    .global_style
+                    .combobox_variant_background(hovered, selected),
+            );
+            context.window.draw_commands.draw_label(
+                suggestion,
+                Vector2::new(row.x + 4., row.y + 2.),
+                context.global_style.text(true),
+            );
+
+            if hovered && context.input.click_up() {
+                completion.selected = n;
+                clicked = true;
+            }
         }
+        ui.end_modal();
+
+        clicked
+    }
+
+    /// Keys typed into the open find bar, false for the ones left to the text
+    fn find_bar_input(
+        &self,
+        input: &InputCharacter,
+        text: &mut String,
+        state: &mut EditboxState,
+    ) -> bool {
+        use KeyCode::*;
+
+        let mut find = match state.find.take() {
+            Some(find) => find,
+            None => return false,
+        };
+
+        let action = match input {
+            InputCharacter {
+                key: Key::Char(character),
+                modifier_ctrl: false,
+                ..
+            } if !character.is_control() => {
+                if find.editing_replacement {
+                    find.replacement.push(*character);
+                    None
+                } else {
+                    find.query.push(*character);
+                    Some(FindAction::Search)
+                }
+            }
+            InputCharacter {
+                key: Key::KeyCode(Backspace),
+                ..
+            } => {
+                if find.editing_replacement {
+                    find.replacement.pop();
+                    None
+                } else {
+                    find.query.pop();
+                    Some(FindAction::Search)
+                }
+            }
+            InputCharacter {
+                key: Key::KeyCode(Tab),
+                ..
+            } => {
+                find.editing_replacement = find.replacing && !find.editing_replacement;
+                None
+            }
+            InputCharacter {
+                key: Key::KeyCode(Enter),
+                modifier_ctrl: true,
+                ..
+            } if find.replacing => Some(FindAction::ReplaceAll),
+            InputCharacter {
+                key: Key::KeyCode(Enter),
+                ..
+            } if find.editing_replacement => Some(FindAction::Replace),
+            InputCharacter {
+                key: Key::KeyCode(Enter),
+                modifier_shift,
+                ..
+            } => Some(if *modifier_shift {
+                FindAction::Previous
+            } else {
+                FindAction::Next
+            }),
+            // closes the bar, the editbox keeps the focus
+            InputCharacter {
+                key: Key::KeyCode(Escape),
+                ..
+            } => return true,
+            _ => {
+                state.find = Some(find);
+                return false;
+            }
+        };
+
+        if let Some(action) = action {
+            self.apply_find_action(action, text, state, &mut find);
+        }
+        state.find = Some(find);
+
+        true
+    }
+
+    /// Height of the find bar, with the replacement row if it is shown
+    fn find_bar_height(&self, find: &Find) -> f32 {
+        let rows = if find.replacing { 2. } else { 1. };
+
+        rows * (self.line_height + FIND_BAR_PADDING * 2.)
+    }
+
+    /// Draw the find bar into `rect` at the bottom of the editbox, returns what was clicked
+    fn draw_find_bar(
+        &self,
+        context: &mut WindowContext,
+        find: &Find,
+        rect: Rect,
+        input_focused: bool,
+    ) -> Option<FindAction> {
+        let style = context.global_style;
+        let focused = context.focused;
+        let mouse = context.input.mouse_position;
+        let click = focused && context.input.click_down();
+        let mouse_down = context.input.is_mouse_down();
+        let draw_commands = &mut context.window.draw_commands;
+        let row_height = self.line_height + FIND_BAR_PADDING * 2.;
+
+        draw_commands.draw_rect(rect, None, style.background(focused));
+        draw_commands.draw_line(
+            Vector2::new(rect.x, rect.y),
+            Vector2::new(rect.x + rect.w, rect.y),
+            style.window_border(focused),
+        );
+
+        let rows = [
+            (
+                "Find",
+                &find.query,
+                !find.editing_replacement,
+                FindAction::EditQuery,
+                [("<", FindAction::Previous), (">", FindAction::Next)],
+            ),
+            (
+                "With",
+                &find.replacement,
+                find.editing_replacement,
+                FindAction::EditReplacement,
+                [
+                    ("Replace", FindAction::Replace),
+                    ("All", FindAction::ReplaceAll),
+                ],
+            ),
+        ];
+        let label_width = rows
+            .iter()
+            .map(|(label, ..)| draw_commands.label_size(label, None).x)
+            .fold(0., f32::max);
+        let rows_shown = if find.replacing { 2 } else { 1 };
+
+        let mut clicked = None;
+        for (n, (label, value, editing, edit, buttons)) in rows.iter().take(rows_shown).enumerate()
+        {
+            let y = rect.y + n as f32 * row_height;
+            let text_y = y + FIND_BAR_PADDING + 1.;
+
+            draw_commands.draw_label(
+                label,
+                Vector2::new(rect.x + 4., text_y),
+                style.text(focused),
+            );
+
+            let mut right = rect.x + rect.w - 2.;
+            for (button_label, action) in buttons.iter().rev() {
+                let width = draw_commands.label_size(button_label, None).x + 8.;
+                right -= width + 2.;
+
+                let button = Rect::new(right, y + 2., width, row_height - 4.);
+                let hovered = button.contains(mouse);
+                draw_commands.draw_rect(
+                    button,
+                    None,
+                    style.button_background(focused, hovered, hovered && mouse_down),
+                );
+                draw_commands.draw_label(
+                    button_label,
+                    Vector2::new(button.x + 4., text_y),
+                    style.text(focused),
+                );
+                if click && hovered {
+                    clicked = Some(*action);
+                }
+            }
+
+            let field_x = rect.x + label_width + 8.;
+            let field = Rect::new(field_x, y + 2., right - 2. - field_x, row_height - 4.);
+            draw_commands.draw_rect(field, None, style.editbox_background(focused));
+
+            draw_commands.push_clip(field);
+            draw_commands.draw_label(
+                value,
+                Vector2::new(field.x + 2., text_y),
+                style.text(focused),
+            );
+            if *editing && input_focused {
+                let cursor_x = field.x + 2. + draw_commands.label_size(value, None).x;
+                draw_commands.draw_rect(
+                    Rect::new(cursor_x, text_y - 2., CURSOR_WIDTH, 13.),
+                    style.editbox_cursor(focused, input_focused),
+                    None,
+                );
+            }
+            draw_commands.pop_clip();
+
+            if click && field.contains(mouse) {
+                clicked = Some(*edit);
+            }
+        }
+
+        clicked
     }
 
     fn apply_keyboard_input(
@@ -77,10 +731,18 @@ impl<'a> Editbox<'a> {
         clipboard: &mut dyn crate::ClipboardObject,
         text: &mut String,
         state: &mut EditboxState,
+        response: &mut EditboxResponse,
+        layout: &dyn Fn(&str) -> TextLayout,
     ) {
         for character in input_buffer.drain(0..) {
             use KeyCode::*;
 
+            if self.completion_input(&character, text, state)
+                || self.find_bar_input(&character, text, state)
+            {
+                continue;
+            }
+
             match character {
                 InputCharacter {
                     key: Key::Char(_),
@@ -92,15 +754,15 @@ impl<'a> Editbox<'a> {
                     modifier_ctrl: false,
                     ..
                 } => {
-                    if character != 13 as char
-                        && character != 10 as char
-                        && character.is_ascii()
+                    if !character.is_control()
                         && self.filter.as_ref().map_or(true, |f| f(character))
                     {
                         if state.selection.is_some() {
                             state.delete_selected(text);
                         }
-                        state.insert_character(text, character);
+                        if self.room_left(text) > 0 {
+                            state.insert_character(text, character);
+                        }
                     }
                 }
                 InputCharacter {
@@ -117,11 +779,12 @@ impl<'a> Editbox<'a> {
                 } => {
                     state.redo(text);
                 }
+                // password selection is not copied, so nothing should be lost
                 InputCharacter {
                     key: Key::KeyCode(X),
                     modifier_ctrl: true,
                     ..
-                } => {
+                } if !self.password => {
                     state.delete_selected(text);
                 }
                 InputCharacter {
@@ -135,13 +798,17 @@ impl<'a> Editbox<'a> {
                                 state.delete_selected(text);
                             }
 
-                            if let Some(filter) = &self.filter {
-                                for character in clipboard.chars() {
-                                    if filter(character) {
-                                        state.insert_character(text, character);
-                                    }
-                                }
-                            } else {
+                            let filter = |character: &char| {
+                                self.filter
+                                    .as_ref()
+                                    .map_or(true, |filter| filter(*character))
+                            };
+                            let clipboard: String = clipboard
+                                .chars()
+                                .filter(filter)
+                                .take(self.room_left(text))
+                                .collect();
+                            if !clipboard.is_empty() {
                                 state.insert_string(text, clipboard);
                             }
                         }
@@ -154,13 +821,71 @@ impl<'a> Editbox<'a> {
                 } => {
                     state.select_all(text);
                 }
+                InputCharacter {
+                    key: Key::KeyCode(F),
+                    modifier_ctrl: true,
+                    ..
+                } if self.multiline && !self.password => {
+                    state.open_find(text, false);
+                }
+                InputCharacter {
+                    key: Key::KeyCode(H),
+                    modifier_ctrl: true,
+                    ..
+                } if self.multiline && !self.password => {
+                    state.open_find(text, true);
+                }
                 InputCharacter {
                     key: Key::KeyCode(Enter),
+                    modifier_ctrl,
                     ..
                 } => {
-                    if self.multiline {
-                        state.insert_character(text, '\n');
+                    if !self.multiline || modifier_ctrl {
+                        response.submitted = true;
+                    } else if self.room_left(text) > 0 {
+                        // the new line keeps the indentation of the current one
+                        let indent = state
+                            .line_indent(text)
+                            .chars()
+                            .take(self.room_left(text) - 1);
+                        let line = std::iter::once('\n').chain(indent).collect();
+                        state.insert_string(text, line);
+                    }
+                }
+                InputCharacter {
+                    key: Key::KeyCode(Tab),
+                    modifier_shift: true,
+                    ..
+                } if self.multiline => {
+                    state.outdent_lines(text, TAB_WIDTH);
+                }
+                InputCharacter {
+                    key: Key::KeyCode(Tab),
+                    ..
+                } if self.multiline => match state.selected_text(text) {
+                    Some(selected) => {
+                        let lines = selected.matches('\n').count() + 1;
+                        if self.room_left(text) >= lines * TAB_WIDTH {
+                            state.indent_lines(text, &" ".repeat(TAB_WIDTH));
+                        }
                     }
+                    // spaces up to the next tab stop
+                    None => {
+                        let cursor = state.cursor as usize;
+                        let line_begin = cursor - state.find_line_begin(text) as usize;
+                        let column = text[line_begin..cursor].chars().count();
+                        let spaces = (TAB_WIDTH - column % TAB_WIDTH).min(self.room_left(text));
+
+                        if spaces > 0 {
+                            state.insert_string(text, " ".repeat(spaces));
+                        }
+                    }
+                },
+                InputCharacter {
+                    key: Key::KeyCode(Escape),
+                    ..
+                } => {
+                    response.cancelled = true;
                 }
                 InputCharacter {
                     key: Key::KeyCode(Backspace),
@@ -209,54 +934,53 @@ impl<'a> Editbox<'a> {
                     modifier_shift,
                     ..
                 } => {
-                    let to_line_begin = state.find_line_begin(&text) as i32;
-                    state.move_cursor(text, -to_line_begin, modifier_shift);
+                    let line_begin = state.cursor - state.find_line_begin(text);
+                    state.move_cursor_to(text, line_begin, modifier_shift);
                 }
                 InputCharacter {
                     key: Key::KeyCode(End),
                     modifier_shift,
                     ..
                 } => {
-                    let to_line_end = state.find_line_end(&text) as i32;
-                    state.move_cursor(text, to_line_end, modifier_shift);
+                    let line_end = state.cursor + state.find_line_end(text);
+                    state.move_cursor_to(text, line_end, modifier_shift);
                 }
                 InputCharacter {
                     key: Key::KeyCode(Up),
                     modifier_shift,
                     ..
                 } => {
-                    let to_line_begin = state.find_line_begin(&text) as i32;
-                    state.move_cursor(text, -to_line_begin, modifier_shift);
-                    if state.cursor != 0 {
-                        state.move_cursor(text, -1, modifier_shift);
-                        let new_to_line_begin = state.find_line_begin(&text) as i32;
-                        let offset = to_line_begin.min(new_to_line_begin) - new_to_line_begin;
-                        state.move_cursor(text, offset, modifier_shift);
-                    }
+                    let layout = layout(text);
+                    let (line, x) = layout.find(state.cursor as usize);
+                    let cursor = match line {
+                        0 => 0,
+                        _ => layout.index_at(line - 1, x),
+                    };
+                    state.move_cursor_to(text, cursor as u32, modifier_shift);
                 }
                 InputCharacter {
                     key: Key::KeyCode(Down),
                     modifier_shift,
                     ..
                 } => {
-                    let to_line_begin = state.find_line_begin(&text) as i32;
-                    let to_line_end = state.find_line_end(&text) as i32;
-
-                    state.move_cursor(text, to_line_end, modifier_shift);
-                    if text.len() != 0 && state.cursor < text.len() as u32 - 1 {
-                        state.move_cursor(text, 1, modifier_shift);
-                        state.move_cursor_within_line(text, to_line_begin, modifier_shift);
-                    }
+                    let layout = layout(text);
+                    let (line, x) = layout.find(state.cursor as usize);
+                    let cursor = if line + 1 < layout.lines.len() {
+                        layout.index_at(line + 1, x)
+                    } else {
+                        text.len()
+                    };
+                    state.move_cursor_to(text, cursor as u32, modifier_shift);
                 }
                 _ => {}
             }
         }
     }
 
-    pub fn ui(self, ui: &mut Ui, text: &mut String) -> bool {
+    pub fn ui(self, ui: &mut Ui, text: &mut String) -> EditboxResponse {
         let time = ui.time;
 
-        let context = ui.get_active_window_context();
+        let mut context = ui.get_active_window_context();
 
         let pos = self
             .pos
@@ -264,12 +988,23 @@ impl<'a> Editbox<'a> {
 
         let rect = Rect::new(pos.x, pos.y, self.size.x, self.size.y);
 
-        let hovered = rect.contains(context.input.mouse_position);
+        let mouse = context.input.mouse_position;
+        let hovered = rect.contains(mouse);
+        let popup_hovered = context
+            .storage_any
+            .get_or_default::<EditboxState>(hash!(self.id, "cursor"))
+            .completion
+            .as_ref()
+            .map_or(false, |completion| completion.rect.contains(mouse));
 
         if context.input.click_down() && hovered {
             context.window.input_focus = Some(self.id);
         }
-        if context.window.input_focused(self.id) && context.input.click_down() && hovered == false {
+        if context.window.input_focused(self.id)
+            && context.input.click_down()
+            && hovered == false
+            && !popup_hovered
+        {
             context.window.input_focus = None;
         }
 
@@ -277,22 +1012,24 @@ impl<'a> Editbox<'a> {
             .storage_any
             .get_or_default::<EditboxState>(hash!(self.id, "cursor"));
 
-        // if text changed outside the selection range should be clamped
+        // if text changed outside the cursor and selection range should be clamped
         state.clamp_selection(text);
+        state.undo_limit = self.undo_limit;
 
         if self.select_all {
             state.select_all(text);
         }
 
         if let Some(selected) = state.selected_text(text) {
-            *context.clipboard_selection = selected.to_owned();
-        }
-        // in case the string was updated outside of editbox
-        if state.cursor > text.len() as u32 {
-            state.cursor = text.len() as u32;
+            // the password is neither copied nor replaced with nothing in the clipboard
+            *context.clipboard_selection = if self.password {
+                None
+            } else {
+                Some(selected.to_owned())
+            };
         }
 
-        let input_focused = context.window.input_focused(self.id) && context.focused;
+        let mut input_focused = context.window.input_focused(self.id) && context.focused;
 
         // reset selection state when lost focus
         if context.focused == false || input_focused == false {
@@ -303,17 +1040,40 @@ impl<'a> Editbox<'a> {
             state.clicks_counter = 0;
         }
 
-        let mut edited = false;
-        if context.focused && input_focused {
-            edited = context.input.input_buffer.len() != 0;
+        let mut response = EditboxResponse::default();
+        let old_cursor = state.cursor;
+        if context.focused && input_focused && !context.input.input_buffer.is_empty() {
+            let old_text = text.clone();
+            let (draw_commands, style) = (&context.window.draw_commands, context.global_style);
             self.apply_keyboard_input(
                 &mut context.input.input_buffer,
                 &mut *context.clipboard,
                 text,
                 &mut state,
+                &mut response,
+                &|text| self.layout(text, draw_commands, style),
             );
+            response.edited = *text != old_text;
+        }
+        if response.cancelled {
+            context.window.input_focus = None;
+            input_focused = false;
+            state.deselect();
+        }
+
+        // the suggestions follow the typed word, moving away from it closes them
+        match self.completer {
+            Some(completer) if input_focused && response.edited => {
+                state.completion = self.complete(completer, text, state);
+            }
+            _ if !input_focused || state.cursor != old_cursor => state.completion = None,
+            _ => {}
         }
 
+        response.gained_focus = input_focused && !state.focused;
+        response.lost_focus = !input_focused && state.focused;
+        state.focused = input_focused;
+
         let color = context.global_style.text(context.focused);
 
         // draw rect in parent window
@@ -324,101 +1084,237 @@ impl<'a> Editbox<'a> {
             None,
         );
 
+        // the find bar takes the bottom of the editbox, the text scrolls above it
+        let mut find = state.find.take();
+        let bar_height = find.as_ref().map_or(0., |find| self.find_bar_height(find));
+        let text_rect = Rect::new(pos.x, pos.y, self.size.x, self.size.y - bar_height);
+
+        if let Some(find) = &mut find {
+            let bar = Rect::new(pos.x, text_rect.y + text_rect.h, self.size.x, bar_height);
+            let clicked = self.draw_find_bar(&mut context, find, bar, input_focused);
+            let state = context
+                .storage_any
+                .get_or_default::<EditboxState>(hash!(self.id, "cursor"));
+
+            if let Some(action) = clicked {
+                let old_text = text.clone();
+                self.apply_find_action(action, text, state, find);
+                response.edited |= *text != old_text;
+            }
+        }
+        context
+            .storage_any
+            .get_or_default::<EditboxState>(hash!(self.id, "cursor"))
+            .find = find;
+        let text_hovered = text_rect.contains(context.input.mouse_position);
+
         // start child window for nice scroll inside the rect
 
         let parent = ui.get_active_window_context();
 
-        let parent_rect = parent.window.content_rect();
-
         parent.window.childs.push(self.id);
         let parent_id = Some(parent.window.id);
 
-        let mut context = ui.begin_window(self.id, parent_id, pos, self.size, 0., false);
+        let size = Vector2::new(text_rect.w, text_rect.h);
+        let mut context = ui.begin_window(self.id, parent_id, pos, size, 0., false);
+        // the window keeps the scroll area it was created with,
+        // while the find bar takes a part of the editbox the text has less space
+        context.window.cursor.area.h = size.y;
+        context.window.cursor.scroll.rect.h = size.y;
+
+        let layout = self.layout(text, &context.window.draw_commands, context.global_style);
+        let size = Vector2::new(
+            layout.gutter + self.text_width(context.global_style, layout.gutter),
+            self.line_height * layout.lines.len() as f32,
+        );
 
-        let size = Vector2::new(150., self.line_height * text.split('\n').count() as f32);
+        context.scroll_area();
 
         let pos = context
             .window
             .cursor
-            .fit(size, Layout::Free(Vector2::new(5., 5.)));
-
-        context.window.draw_commands.clip(parent_rect);
+            .fit(size, Layout::Free(Vector2::new(PADDING, PADDING)));
+        let text_pos = pos + Vector2::new(layout.gutter, 0.);
 
-        context.scroll_area();
-
-        context
-            .window
-            .draw_commands
-            .clip(context.window.content_rect());
+        let content_rect = context.window.content_rect();
+        context.window.draw_commands.push_clip(content_rect);
 
         let state = context
             .storage_any
             .get_or_default::<EditboxState>(hash!(self.id, "cursor"));
 
-        let mut x = LEFT_MARGIN;
-        let mut y = 0.;
-        let mut clicked = false;
+        if text_hovered && context.input.is_mouse_down() && input_focused {
+            let mouse = context.input.mouse_position - text_pos;
+            let line = ((mouse.y / self.line_height).max(0.) as usize).min(layout.lines.len() - 1);
+            let index = layout.index_at(line, mouse.x + state.scroll_x) as u32;
 
-        for n in 0..text.len() + 1 {
-            let character = text.chars().nth(n).unwrap_or(' ');
-            if n == state.cursor as usize {
-                context.window.draw_commands.draw_rect(
-                    Rect::new(pos.x + x, pos.y + y - 2., 2., 13.),
-                    context
-                        .global_style
-                        .editbox_cursor(context.focused, input_focused),
-                    None,
-                );
+            if context.input.click_down() {
+                state.completion = None;
+                state.click_down(time, text, index);
+            } else {
+                state.click_move(text, index);
             }
-            let mut advance = 1.5; // 1.5 - hack to make cursor on newlines visible
-            if character != '\n' {
-                advance = context
-                    .window
-                    .draw_commands
-                    .draw_character(character, pos + Vector2::new(x, y), color)
-                    .unwrap_or(0.);
+        }
+
+        // keep the cursor inside the visible part of the line
+        if self.multiline && self.wrap {
+            state.scroll_x = 0.;
+        } else {
+            let text_width = self.text_width(context.global_style, layout.gutter);
+            let (_, cursor_x) = layout.find(state.cursor as usize);
+            let max_scroll = (layout.width() - LEFT_MARGIN - text_width).max(0.);
+
+            state.scroll_x = state
+                .scroll_x
+                .max(cursor_x - LEFT_MARGIN - text_width)
+                .min(cursor_x - LEFT_MARGIN)
+                .min(max_scroll)
+                .max(0.);
+        }
+
+        if state.reveal_cursor {
+            state.reveal_cursor = false;
+
+            let (line, _) = layout.find(state.cursor as usize);
+            let top = PADDING + line as f32 * self.line_height;
+            let bottom = top + self.line_height;
+            let scroll = &mut context.window.cursor.scroll;
+
+            if top < scroll.rect.y {
+                scroll.scroll_to(top - PADDING);
+            } else if bottom > scroll.rect.y + scroll.rect.h {
+                scroll.scroll_to(bottom + PADDING - scroll.rect.h);
             }
-            if state.in_selected_range(n as u32) {
-                let pos = pos + Vector2::new(x, y);
+        }
 
-                context.window.draw_commands.draw_rect(
-                    Rect::new(pos.x, pos.y - 2., advance, 13.),
-                    None,
-                    context.global_style.selection_background(context.focused),
+        let mask = if context.window.draw_commands.has_glyph('•') {
+            '•'
+        } else {
+            '*'
+        };
+
+        if let (true, Some(placeholder)) = (text.is_empty(), self.placeholder) {
+            context.window.draw_commands.draw_label(
+                placeholder,
+                text_pos + Vector2::new(LEFT_MARGIN, 0.),
+                context.global_style.editbox_placeholder(context.focused),
+            );
+        }
+
+        if layout.gutter > 0. {
+            let mut number = 0;
+            for (n, line) in layout.lines.iter().enumerate() {
+                // wrapped parts of a line are not numbered
+                let start = line.first().map_or(0, |caret| caret.index);
+                if start != 0 && !text[..start].ends_with('\n') {
+                    continue;
+                }
+                number += 1;
+
+                let label = number.to_string();
+                let draw_commands = &context.window.draw_commands;
+                let width: f32 = label
+                    .chars()
+                    .map(|character| draw_commands.character_advance(character))
+                    .sum();
+                context.window.draw_commands.draw_label(
+                    &label,
+                    pos + Vector2::new(
+                        layout.gutter - GUTTER_PADDING / 2. - width,
+                        n as f32 * self.line_height,
+                    ),
+                    context.global_style.editbox_line_number(context.focused),
                 );
             }
 
-            if clicked == false && hovered && context.input.is_mouse_down() && input_focused {
-                let cursor_on_current_line =
-                    (context.input.mouse_position.y - (pos.y + y + self.line_height / 2.)).abs()
-                        < self.line_height / 2. + 0.1;
-                let line_end = character == '\n' || n == text.len();
-                let cursor_after_line_end = context.input.mouse_position.x > (pos.x + x);
-                let clickable_character = character != '\n';
-                let cursor_on_character =
-                    (context.input.mouse_position.x - (pos.x + x)).abs() < advance / 2.;
-                let last_character = n == text.len();
-                let cursor_below_line =
-                    (context.input.mouse_position.y - (pos.y + y + self.line_height)) > 0.;
-
-                if (cursor_on_current_line && line_end && cursor_after_line_end)
-                    || (cursor_on_current_line && clickable_character && cursor_on_character)
-                    || (last_character && cursor_below_line)
-                {
-                    clicked = true;
+            // scrolled text should not go under the line numbers
+            context.window.draw_commands.push_clip(Rect::new(
+                text_pos.x,
+                content_rect.y,
+                content_rect.x + content_rect.w - text_pos.x,
+                content_rect.h,
+            ));
+        }
 
-                    if context.input.click_down() {
-                        state.click_down(time, text, n as u32);
-                    } else {
-                        state.click_move(text, n as u32);
+        let brackets = if self.match_brackets && input_focused {
+            state.matching_brackets(text)
+        } else {
+            None
+        };
+
+        let matches = state
+            .find
+            .as_ref()
+            .map_or(vec![], |find| find_matches(text, &find.query));
+        let mut matches = matches.iter().peekable();
+
+        let highlights = match self.highlighter {
+            Some(highlighter) if !self.password => highlighter.highlight(text),
+            _ => vec![],
+        };
+        let mut highlights = highlights.iter().peekable();
+
+        for (n, line) in layout.lines.iter().enumerate() {
+            let y = n as f32 * self.line_height;
+
+            for caret in line {
+                let pos = text_pos + Vector2::new(caret.x - state.scroll_x, y);
+
+                if caret.index == state.cursor as usize {
+                    context.window.draw_commands.draw_rect(
+                        Rect::new(pos.x, pos.y - 2., CURSOR_WIDTH, 13.),
+                        context
+                            .global_style
+                            .editbox_cursor(context.focused, input_focused),
+                        None,
+                    );
+                }
+                if caret.character != '\n' && caret.index != text.len() {
+                    let character = if self.password { mask } else { caret.character };
+                    // the carets go in the text order, so do the highlighted ranges
+                    while highlights
+                        .peek()
+                        .map_or(false, |(range, _)| range.end <= caret.index)
+                    {
+                        highlights.next();
                     }
+                    let color = match highlights.peek() {
+                        Some((range, color)) if range.contains(&caret.index) => *color,
+                        _ => color,
+                    };
+                    context
+                        .window
+                        .draw_commands
+                        .draw_character(character, pos, color);
+                }
+                let index = caret.index as u32;
+                if brackets.map_or(false, |(a, b)| index == a || index == b) {
+                    context.window.draw_commands.draw_rect(
+                        Rect::new(pos.x, pos.y - 2., caret.advance, 13.),
+                        context.global_style.editbox_bracket_match(context.focused),
+                        None,
+                    );
+                }
+                while matches.peek().map_or(false, |(_, end)| *end <= index) {
+                    matches.next();
+                }
+                if matches
+                    .peek()
+                    .map_or(false, |(start, end)| (*start..*end).contains(&index))
+                {
+                    context.window.draw_commands.draw_rect(
+                        Rect::new(pos.x, pos.y - 2., caret.advance, 13.),
+                        None,
+                        context.global_style.editbox_find_match(context.focused),
+                    );
+                }
+                if state.in_selected_range(index) {
+                    context.window.draw_commands.draw_rect(
+                        Rect::new(pos.x, pos.y - 2., caret.advance, 13.),
+                        None,
+                        context.global_style.selection_background(context.focused),
+                    );
                 }
-            }
-
-            x += advance;
-            if character == '\n' && self.multiline {
-                y += self.line_height;
-                x = LEFT_MARGIN;
             }
         }
 
@@ -426,18 +1322,79 @@ impl<'a> Editbox<'a> {
             state.click_up(text);
         }
 
+        // under the line with the cursor, the suggestions start where the word does
+        if let Some(completion) = &mut state.completion {
+            let (line, _) = layout.find(state.cursor as usize);
+            let (_, word_x) = layout.find(completion.word_begin as usize);
+            let draw_commands = &context.window.draw_commands;
+            let width = completion
+                .suggestions
+                .iter()
+                .map(|suggestion| draw_commands.label_size(suggestion, None).x)
+                .fold(0., f32::max);
+            let rows = completion.suggestions.len().min(MAX_SUGGESTIONS);
+
+            completion.rect = Rect::new(
+                text_pos.x + word_x - state.scroll_x - 4.,
+                text_pos.y + (line + 1) as f32 * self.line_height + 2.,
+                width + 8.,
+                rows as f32 * (self.line_height + 4.),
+            );
+        }
+
         let context = ui.get_active_window_context();
 
-        context.window.draw_commands.clip(None);
+        if layout.gutter > 0. {
+            context.window.draw_commands.pop_clip();
+        }
+        context.window.draw_commands.pop_clip();
 
         ui.end_window();
 
-        edited
+        let state = ui
+            .get_active_window_context()
+            .storage_any
+            .get_or_default::<EditboxState>(hash!(self.id, "cursor"));
+        if let Some(mut completion) = state.completion.take() {
+            let clicked = self.draw_completion_popup(ui, &mut completion);
+            let state = ui
+                .get_active_window_context()
+                .storage_any
+                .get_or_default::<EditboxState>(hash!(self.id, "cursor"));
+
+            if clicked {
+                let old_text = text.clone();
+                self.accept_completion(text, state, &completion);
+                response.edited |= *text != old_text;
+            } else {
+                state.completion = Some(completion);
+            }
+        }
+
+        ui.get_active_window_context()
+            .storage_any
+            .get_or_default::<EditboxState>(hash!(self.id, "cursor"))
+            .text_seen(text);
+
+        response
     }
 }
 
 impl Ui {
+    /// Returns true if the text was edited, see `Editbox::ui` for the other events
     pub fn editbox(&mut self, id: Id, size: Vector2, text: &mut String) -> bool {
-        Editbox::new(id, size).ui(self, text)
+        Editbox::new(id, size).ui(self, text).edited
+    }
+
+    /// Undo and redo steps of the editbox, e.g. to show them in the app's Edit menu
+    pub fn editbox_history(&mut self, id: Id) -> EditboxHistory {
+        self.get_any::<EditboxState>(hash!(id, "cursor")).history()
+    }
+
+    /// Forget the undo and redo steps of the editbox, e.g. after saving a file.
+    /// Changing the text outside of the editbox forgets them too.
+    pub fn clear_editbox_history(&mut self, id: Id) {
+        self.get_any::<EditboxState>(hash!(id, "cursor"))
+            .clear_history();
     }
 }
@@ -0,0 +1,14 @@
+//! Suggestions for the word being typed, like console commands or asset paths.
+
+/// Suggests the words to complete the one before the cursor
+pub trait Completer {
+    /// Suggestions for the `word` between its beginning and the cursor,
+    /// the accepted one replaces the whole `word`.
+    fn complete(&self, word: &str) -> Vec<String>;
+}
+
+impl<F: Fn(&str) -> Vec<String>> Completer for F {
+    fn complete(&self, word: &str) -> Vec<String> {
+        self(word)
+    }
+}
@@ -0,0 +1,136 @@
+//! Coloring the editbox text, e.g. for the small scripts and shader snippets.
+
+use crate::types::Color;
+
+use std::ops::Range;
+
+/// Colors parts of the editbox text
+pub trait Highlighter {
+    /// Byte ranges of the text with their colors, sorted and not overlapping.
+    /// The rest of the text is drawn with the style's text color.
+    fn highlight(&self, text: &str) -> Vec<(Range<usize>, Color)>;
+}
+
+/// Keywords, numbers, quoted strings and line comments
+pub struct SyntaxHighlighter<'a> {
+    keywords: &'a [&'a str],
+    line_comment: Option<&'a str>,
+    keyword_color: Color,
+    number_color: Color,
+    string_color: Color,
+    comment_color: Color,
+}
+
+impl<'a> SyntaxHighlighter<'a> {
+    pub fn new(keywords: &'a [&'a str]) -> SyntaxHighlighter<'a> {
+        SyntaxHighlighter {
+            keywords,
+            line_comment: None,
+            keyword_color: Color::from_rgb(40, 70, 200),
+            number_color: Color::from_rgb(170, 90, 0),
+            string_color: Color::from_rgb(20, 130, 40),
+            comment_color: Color::from_rgb(130, 130, 130),
+        }
+    }
+
+    /// Start of a comment lasting until the end of the line, like "//" or "#"
+    pub fn line_comment(self, line_comment: &'a str) -> Self {
+        Self {
+            line_comment: Some(line_comment),
+            ..self
+        }
+    }
+
+    pub fn keyword_color(self, keyword_color: Color) -> Self {
+        Self {
+            keyword_color,
+            ..self
+        }
+    }
+
+    pub fn number_color(self, number_color: Color) -> Self {
+        Self {
+            number_color,
+            ..self
+        }
+    }
+
+    pub fn string_color(self, string_color: Color) -> Self {
+        Self {
+            string_color,
+            ..self
+        }
+    }
+
+    pub fn comment_color(self, comment_color: Color) -> Self {
+        Self {
+            comment_color,
+            ..self
+        }
+    }
+}
+
+fn is_word(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+/// Byte offset of the first character after `start` not matching the predicate
+fn skip_while(text: &str, start: usize, predicate: impl Fn(char) -> bool) -> usize {
+    text[start..]
+        .find(|character| !predicate(character))
+        .map_or(text.len(), |n| start + n)
+}
+
+impl<'a> Highlighter for SyntaxHighlighter<'a> {
+    fn highlight(&self, text: &str) -> Vec<(Range<usize>, Color)> {
+        let mut ranges = vec![];
+        let mut position = 0;
+
+        while let Some(character) = text[position..].chars().next() {
+            let start = position;
+
+            match character {
+                _ if self.line_comment.map_or(false, |comment| {
+                    !comment.is_empty() && text[start..].starts_with(comment)
+                }) =>
+                {
+                    position = skip_while(text, start, |character| character != '\n');
+                    ranges.push((start..position, self.comment_color));
+                }
+                '"' | '\'' => {
+                    // until the closing quote, not escaped with a backslash, or the end of the line
+                    let mut escaped = false;
+                    position = text.len();
+                    for (n, next) in text[start + 1..].char_indices() {
+                        let n = start + 1 + n;
+                        if next == '\n' {
+                            position = n;
+                            break;
+                        }
+                        if next == character && !escaped {
+                            position = n + 1;
+                            break;
+                        }
+                        escaped = next == '\\' && !escaped;
+                    }
+                    ranges.push((start..position, self.string_color));
+                }
+                _ if character.is_ascii_digit() => {
+                    position = skip_while(text, start, |character| {
+                        is_word(character) || character == '.'
+                    });
+                    ranges.push((start..position, self.number_color));
+                }
+                _ if is_word(character) => {
+                    position = skip_while(text, start, is_word);
+                    if self.keywords.contains(&&text[start..position]) {
+                        ranges.push((start..position, self.keyword_color));
+                    }
+                }
+                _ => position += character.len_utf8(),
+            }
+        }
+
+        ranges
+    }
+}
@@ -1,6 +1,54 @@
+//! Cursor and selection positions are byte offsets in the text.
+//! They always stay on character boundaries, combining marks are kept together
+//! with the character they modify, so a single keystroke never splits them.
+
+use crate::types::Rect;
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
 trait Command {
     fn apply(&self, text_cursor: &mut u32, text: &mut String);
     fn unapply(&self, text_cursor: &mut u32, text: &mut String);
+    fn edit(&self) -> TextEdit;
+}
+
+/// A change of the editbox text: `removed` at the byte `position` was replaced with `inserted`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub position: u32,
+    pub removed: String,
+    pub inserted: String,
+}
+
+/// Undo and redo steps of an editbox, each with its edits in the order they were made.
+/// The last step of each stack is the first to be undone or redone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditboxHistory {
+    pub undo: Vec<Vec<TextEdit>>,
+    pub redo: Vec<Vec<TextEdit>>,
+}
+
+/// Commands undone and redone together
+type Step = Vec<Box<dyn Command>>;
+
+/// Single character edits merged into one undo step while they go on in a row
+#[derive(Clone, Copy, PartialEq)]
+enum EditKind {
+    Typing,
+    Backspace,
+    Delete,
+}
+
+/// The last undo step, the next edit of the same kind right at the cursor joins it
+struct OpenStep {
+    kind: EditKind,
+    /// Cursor after the last edit
+    cursor: u32,
+    /// The last typed or deleted character
+    character: char,
 }
 
 struct InsertCharacter {
@@ -23,7 +71,7 @@ impl Command for InsertCharacter {
         if self.cursor <= text.len() as u32 {
             text.insert(self.cursor as usize, self.character);
         }
-        *text_cursor += 1;
+        *text_cursor += self.character.len_utf8() as u32;
     }
     fn unapply(&self, text_cursor: &mut u32, text: &mut String) {
         *text_cursor = self.cursor;
@@ -31,6 +79,13 @@ impl Command for InsertCharacter {
             text.remove(self.cursor as usize);
         }
     }
+    fn edit(&self) -> TextEdit {
+        TextEdit {
+            position: self.cursor,
+            removed: String::new(),
+            inserted: self.character.to_string(),
+        }
+    }
 }
 
 struct InsertString {
@@ -64,20 +119,34 @@ impl Command for InsertString {
             text.replace_range(self.cursor as usize..end, "");
         }
     }
+
+    fn edit(&self) -> TextEdit {
+        TextEdit {
+            position: self.cursor,
+            removed: String::new(),
+            inserted: self.data.clone(),
+        }
+    }
 }
 
+/// Deletes the character after the cursor with all its combining marks
 struct DeleteCharacter {
-    character: char,
+    data: String,
     cursor: u32,
 }
 
 impl DeleteCharacter {
     fn new(editor: &EditboxState, text: &mut String) -> Option<DeleteCharacter> {
-        let character = text.chars().nth(editor.cursor as usize);
+        let start = editor.cursor as usize;
+        let end = next_boundary(text, start);
 
-        character.map(|character| DeleteCharacter {
+        if start == end {
+            return None;
+        }
+
+        Some(DeleteCharacter {
             cursor: editor.cursor,
-            character,
+            data: text[start..end].to_string(),
         })
     }
 }
@@ -86,14 +155,24 @@ impl Command for DeleteCharacter {
     fn apply(&self, text_cursor: &mut u32, text: &mut String) {
         *text_cursor = self.cursor;
         if self.cursor < text.len() as u32 {
-            text.remove(self.cursor as usize);
+            let end = (self.cursor as usize + self.data.len()).min(text.len());
+
+            text.replace_range(self.cursor as usize..end, "");
         }
     }
 
     fn unapply(&self, text_cursor: &mut u32, text: &mut String) {
-        *text_cursor = self.cursor + 1;
+        *text_cursor = self.cursor + self.data.len() as u32;
         if self.cursor <= text.len() as u32 {
-            text.insert(self.cursor as usize, self.character);
+            text.insert_str(self.cursor as usize, &self.data);
+        }
+    }
+
+    fn edit(&self) -> TextEdit {
+        TextEdit {
+            position: self.cursor,
+            removed: self.data.clone(),
+            inserted: String::new(),
         }
     }
 }
@@ -132,6 +211,47 @@ impl Command for DeleteRange {
         text.insert_str(start as usize, &self.data);
         *text_cursor = start;
     }
+
+    fn edit(&self) -> TextEdit {
+        let (start, end) = self.range;
+
+        TextEdit {
+            position: start.min(end),
+            removed: self.data.clone(),
+            inserted: String::new(),
+        }
+    }
+}
+
+/// Replaces the text at `start`, used to change several lines in one step
+struct ReplaceRange {
+    start: u32,
+    old: String,
+    new: String,
+}
+
+impl Command for ReplaceRange {
+    fn apply(&self, text_cursor: &mut u32, text: &mut String) {
+        let start = self.start as usize;
+
+        text.replace_range(start..start + self.old.len(), &self.new);
+        *text_cursor = self.start + self.new.len() as u32;
+    }
+
+    fn unapply(&self, text_cursor: &mut u32, text: &mut String) {
+        let start = self.start as usize;
+
+        text.replace_range(start..start + self.new.len(), &self.old);
+        *text_cursor = self.start + self.old.len() as u32;
+    }
+
+    fn edit(&self) -> TextEdit {
+        TextEdit {
+            position: self.start,
+            removed: self.old.clone(),
+            inserted: self.new.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -149,6 +269,38 @@ impl Default for ClickState {
 }
 pub const DOUBLE_CLICK_TIME: f32 = 0.5;
 
+/// Find and replace bar of the multiline editbox
+#[derive(Default)]
+pub struct Find {
+    pub query: String,
+    pub replacement: String,
+    /// The replacement row is shown
+    pub replacing: bool,
+    /// Typing goes to the replacement instead of the query
+    pub editing_replacement: bool,
+}
+
+/// Suggestions popup for the word before the cursor
+pub struct Completion {
+    pub suggestions: Vec<String>,
+    pub selected: usize,
+    /// Byte offset of the word being completed, it lasts until the cursor
+    pub word_begin: u32,
+    /// Where the popup was drawn, clicks inside it keep the input focus
+    pub rect: Rect,
+}
+
+/// Byte ranges of the not overlapping occurrences of the query
+pub fn find_matches(text: &str, query: &str) -> Vec<(u32, u32)> {
+    if query.is_empty() {
+        return vec![];
+    }
+
+    text.match_indices(query)
+        .map(|(n, found)| (n as u32, (n + found.len()) as u32))
+        .collect()
+}
+
 #[derive(Default)]
 pub struct EditboxState {
     pub cursor: u32,
@@ -158,20 +310,43 @@ pub struct EditboxState {
     pub last_click_time: f32,
     pub last_click: u32,
     pub selection: Option<(u32, u32)>,
-    undo_stack: Vec<Box<dyn Command>>,
-    redo_stack: Vec<Box<dyn Command>>,
+    /// Had the input focus on the previous frame
+    pub focused: bool,
+    /// Horizontal scroll of the single line and the not wrapped editboxes
+    pub scroll_x: f32,
+    /// Scroll the cursor into view on the next frame
+    pub reveal_cursor: bool,
+    pub find: Option<Find>,
+    pub completion: Option<Completion>,
+    /// Undo steps kept, the oldest ones are dropped
+    pub undo_limit: usize,
+    undo_stack: Vec<Step>,
+    redo_stack: Vec<Step>,
+    open_step: Option<OpenStep>,
+    /// Hash of the text as the editbox left it, to notice the changes made outside
+    text_hash: u64,
 }
 
 impl EditboxState {
-    pub fn clamp_selection<'a>(&mut self, text: &'a str) {
+    /// Keep the cursor and the selection inside the text and on the character boundaries,
+    /// the text may be changed outside of the editbox
+    pub fn clamp_selection(&mut self, text: &str) {
+        self.cursor = snap(text, self.cursor);
         if let Some((ref mut start, ref mut end)) = &mut self.selection {
-            if *start >= text.len() as u32 {
-                *start = text.len() as _;
-            }
-            if *end >= text.len() as u32 {
-                *end = text.len() as _;
-            }
+            *start = snap(text, *start);
+            *end = snap(text, *end);
         }
+
+        // the suggestions and the undo steps were for the text that may be gone now
+        if hash_text(text) != self.text_hash {
+            self.completion = None;
+            self.clear_history();
+        }
+    }
+
+    /// Remember the text after the frame's editing, the changes after this are not editbox's
+    pub fn text_seen(&mut self, text: &str) {
+        self.text_hash = hash_text(text);
     }
 
     pub fn selected_text<'a>(&self, text: &'a str) -> Option<&'a str> {
@@ -194,26 +369,241 @@ impl EditboxState {
             _ => false,
         }
     }
+
+    /// Distance in bytes from the beginning of the line to the cursor
     pub fn find_line_begin(&self, text: &str) -> u32 {
-        let mut line_position = 0;
-        let mut cursor_tmp = self.cursor;
+        let cursor = self.cursor as usize;
+        let line_begin = text[..cursor].rfind('\n').map_or(0, |n| n + 1);
 
-        while cursor_tmp > 0 && text.chars().nth(cursor_tmp as usize - 1).unwrap_or('x') != '\n' {
-            cursor_tmp -= 1;
-            line_position += 1;
-        }
-        line_position
+        (cursor - line_begin) as u32
     }
 
+    /// Distance in bytes from the cursor to the end of the line
     pub fn find_line_end(&self, text: &str) -> u32 {
-        let mut cursor_tmp = self.cursor;
-        while cursor_tmp < text.len() as u32
-            && text.chars().nth(cursor_tmp as usize).unwrap_or('x') != '\n'
-        {
-            cursor_tmp += 1;
+        let cursor = self.cursor as usize;
+
+        text[cursor..].find('\n').unwrap_or(text.len() - cursor) as u32
+    }
+
+    /// Spaces and tabs the cursor's line starts with, up to the cursor
+    pub fn line_indent<'a>(&self, text: &'a str) -> &'a str {
+        let cursor = self.cursor as usize;
+        let line = &text[cursor - self.find_line_begin(text) as usize..cursor];
+        let indent = line
+            .find(|character| character != ' ' && character != '\t')
+            .unwrap_or(line.len());
+
+        &line[..indent]
+    }
+
+    /// Byte range of the whole lines touched by the cursor and the selection.
+    /// A selection ending right after a newline does not touch the next line.
+    fn touched_lines(&self, text: &str) -> (usize, usize) {
+        let (from, to) = self
+            .selection
+            .map_or((self.cursor, self.cursor), |(start, end)| {
+                (start.min(end), start.max(end))
+            });
+        let (from, mut to) = (from as usize, to as usize);
+        if to > from && text[..to].ends_with('\n') {
+            to -= 1;
+        }
+
+        let begin = text[..from].rfind('\n').map_or(0, |n| n + 1);
+        let end = text[to..].find('\n').map_or(text.len(), |n| to + n);
+
+        (begin, end)
+    }
+
+    /// Change every touched line with `f` as a single undo step.
+    /// Returns the range of the changed lines, `None` if nothing changed.
+    fn replace_lines(
+        &mut self,
+        text: &mut String,
+        f: impl Fn(&str) -> String,
+    ) -> Option<(u32, u32)> {
+        let (begin, end) = self.touched_lines(text);
+        let old = &text[begin..end];
+        let new = old.split('\n').map(f).collect::<Vec<_>>().join("\n");
+
+        if new == old {
+            return None;
+        }
+
+        self.replace_range(text, begin, end, new);
+
+        Some((begin as u32, self.cursor))
+    }
+
+    /// Replace the text between `start` and `end` as a single undo step, the cursor goes after `new`
+    fn replace_range(&mut self, text: &mut String, start: usize, end: usize, new: String) {
+        self.redo_stack.clear();
+
+        let command = ReplaceRange {
+            start: start as u32,
+            old: text[start..end].to_string(),
+            new,
+        };
+        command.apply(&mut self.cursor, text);
+        self.push_command(Box::new(command), false, None);
+    }
+
+    /// Replace the word from `word_begin` to the cursor with the suggestion as a single undo step
+    pub fn complete_word(&mut self, text: &mut String, word_begin: u32, suggestion: &str) {
+        self.selection = None;
+        self.replace_range(
+            text,
+            word_begin as usize,
+            self.cursor as usize,
+            suggestion.to_string(),
+        );
+    }
+
+    /// Show the find bar, searching for the selected text if it fits on one line
+    pub fn open_find(&mut self, text: &str, replacing: bool) {
+        let selected = self
+            .selected_text(text)
+            .filter(|selected| !selected.is_empty() && !selected.contains('\n'))
+            .map(|selected| selected.to_string());
+        let find = self.find.get_or_insert_with(Find::default);
+
+        if let Some(selected) = selected {
+            find.query = selected;
         }
+        find.replacing |= replacing;
+        find.editing_replacement = replacing;
+    }
 
-        cursor_tmp - self.cursor
+    fn select_match(&mut self, (start, end): (u32, u32)) {
+        self.selection = Some((start, end));
+        self.cursor = end;
+        self.reveal_cursor = true;
+    }
+
+    /// Select the first occurrence of the query starting at `from` or after it,
+    /// wrapping around the end of the text
+    pub fn find_from(&mut self, text: &str, query: &str, from: u32) {
+        let matches = find_matches(text, query);
+        let found = matches
+            .iter()
+            .find(|(start, _)| *start >= from)
+            .or_else(|| matches.first());
+
+        if let Some(&found) = found {
+            self.select_match(found);
+        }
+    }
+
+    /// Select the next occurrence of the query after the cursor or the selection
+    pub fn find_next(&mut self, text: &str, query: &str) {
+        let from = self
+            .selection
+            .map_or(self.cursor, |(start, end)| start.max(end));
+
+        self.find_from(text, query, from);
+    }
+
+    /// Select the last occurrence of the query before the cursor or the selection,
+    /// wrapping around the beginning of the text
+    pub fn find_previous(&mut self, text: &str, query: &str) {
+        let before = self
+            .selection
+            .map_or(self.cursor, |(start, end)| start.min(end));
+        let matches = find_matches(text, query);
+        let found = matches
+            .iter()
+            .rev()
+            .find(|(start, _)| *start < before)
+            .or_else(|| matches.last());
+
+        if let Some(&found) = found {
+            self.select_match(found);
+        }
+    }
+
+    /// Replace the selected occurrence of the query and select the next one
+    pub fn replace(&mut self, text: &mut String, query: &str, replacement: &str) {
+        if let (Some((start, end)), false) = (self.selection, query.is_empty()) {
+            let (start, end) = (start.min(end) as usize, start.max(end) as usize);
+
+            if &text[start..end] == query {
+                self.selection = None;
+                self.replace_range(text, start, end, replacement.to_string());
+            }
+        }
+
+        self.find_from(text, query, self.cursor);
+    }
+
+    /// Replace every occurrence of the query as a single undo step
+    pub fn replace_all(&mut self, text: &mut String, query: &str, replacement: &str) {
+        let matches = find_matches(text, query);
+
+        if let (Some(&(start, _)), Some(&(_, end))) = (matches.first(), matches.last()) {
+            let (start, end) = (start as usize, end as usize);
+            let new = text[start..end].replace(query, replacement);
+
+            self.selection = None;
+            self.replace_range(text, start, end, new);
+        }
+    }
+
+    /// Prepend `indent` to every line touched by the selection and select all of them
+    pub fn indent_lines(&mut self, text: &mut String, indent: &str) {
+        if let Some(lines) = self.replace_lines(text, |line| format!("{}{}", indent, line)) {
+            self.selection = Some(lines);
+        }
+    }
+
+    /// Remove up to `width` leading spaces from every line touched by the cursor or the selection
+    pub fn outdent_lines(&mut self, text: &mut String, width: usize) {
+        let leading_spaces = |line: &str| {
+            line.chars()
+                .take(width)
+                .take_while(|character| *character == ' ')
+                .count()
+        };
+
+        let cursor = self.cursor;
+        let line_begin = cursor - self.find_line_begin(text);
+        let removed = leading_spaces(&text[line_begin as usize..]) as u32;
+
+        let lines = self.replace_lines(text, |line| line[leading_spaces(line)..].to_string());
+        match lines {
+            Some(lines) if self.selection.is_some() => self.selection = Some(lines),
+            Some(_) => self.cursor = cursor.saturating_sub(removed).max(line_begin),
+            None => {}
+        }
+    }
+
+    /// Byte offsets of the bracket next to the cursor and its pair.
+    /// The bracket after the cursor is preferred to the one before it.
+    pub fn matching_brackets(&self, text: &str) -> Option<(u32, u32)> {
+        const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+        let cursor = self.cursor as usize;
+        let after = text[cursor..]
+            .chars()
+            .next()
+            .map(|character| (cursor, character));
+        let before = text[..cursor].char_indices().next_back();
+
+        for (position, bracket) in after.into_iter().chain(before) {
+            for &(open, close) in &PAIRS {
+                let pair = if bracket == open {
+                    find_pair(text[position..].char_indices(), open, close).map(|n| position + n)
+                } else if bracket == close {
+                    find_pair(text[..=position].char_indices().rev(), close, open)
+                } else {
+                    continue;
+                };
+
+                if let Some(pair) = pair {
+                    return Some((position as u32, pair as u32));
+                }
+            }
+        }
+        None
     }
 
     pub fn word_delimeter(character: char) -> bool {
@@ -224,38 +614,70 @@ impl EditboxState {
             || character == '\"'
     }
 
+    /// Distance in bytes from the beginning of the word to the `cursor`
     pub fn find_word_begin(&self, text: &str, cursor: u32) -> u32 {
-        let mut cursor_tmp = cursor;
-        let mut offset = 0;
-
-        while cursor_tmp > 0 {
-            let current_char = text.chars().nth(cursor_tmp as usize - 1).unwrap_or(' ');
-            if Self::word_delimeter(current_char) || current_char == '\n' {
-                break;
-            }
-            offset += 1;
-            cursor_tmp -= 1;
-        }
-        offset
+        let cursor = cursor as usize;
+        let word_begin = text[..cursor]
+            .char_indices()
+            .rev()
+            .find(|(_, character)| Self::word_delimeter(*character) || *character == '\n')
+            .map_or(0, |(n, character)| n + character.len_utf8());
+
+        (cursor - word_begin) as u32
     }
 
+    /// Distance in bytes from the `cursor` to the beginning of the next word
     pub fn find_word_end(&self, text: &str, cursor: u32) -> u32 {
-        let mut cursor_tmp = cursor;
-        let mut offset = 0;
+        let cursor = cursor as usize;
         let mut space_skipping = false;
 
-        while cursor_tmp < text.len() as u32 {
-            let current_char = text.chars().nth(cursor_tmp as usize).unwrap_or(' ');
+        for (n, current_char) in text[cursor..].char_indices() {
             if Self::word_delimeter(current_char) || current_char == '\n' {
                 space_skipping = true;
             }
             if space_skipping && Self::word_delimeter(current_char) == false {
-                break;
+                return n as u32;
             }
-            cursor_tmp += 1;
-            offset += 1;
         }
-        offset
+        (text.len() - cursor) as u32
+    }
+
+    fn is_word_boundary(character: char) -> bool {
+        character.is_whitespace() || Self::word_delimeter(character)
+    }
+
+    /// Whether the edit of `character` at the cursor goes on with the last undo step.
+    /// A new step starts where a new word does, so the steps are about a word long.
+    fn continues_step(&self, kind: EditKind, character: char) -> bool {
+        self.open_step.as_ref().map_or(false, |step| {
+            let word_begins =
+                Self::is_word_boundary(step.character) && !Self::is_word_boundary(character);
+
+            step.kind == kind && step.cursor == self.cursor && !word_begins
+        })
+    }
+
+    /// Add the applied command to the history, as a part of the last undo step if `merge`.
+    /// The next edit may join the step if it is a single character `edit`.
+    fn push_command(
+        &mut self,
+        command: Box<dyn Command>,
+        merge: bool,
+        edit: Option<(EditKind, char)>,
+    ) {
+        match self.undo_stack.last_mut() {
+            Some(step) if merge => step.push(command),
+            _ => self.undo_stack.push(vec![command]),
+        }
+        let excess = self.undo_stack.len().saturating_sub(self.undo_limit);
+        self.undo_stack.drain(..excess);
+
+        let cursor = self.cursor;
+        self.open_step = edit.map(|(kind, character)| OpenStep {
+            kind,
+            cursor,
+            character,
+        });
     }
 
     pub fn insert_character(&mut self, text: &mut String, character: char) {
@@ -263,11 +685,17 @@ impl EditboxState {
 
         self.selection = None;
 
+        let merge = self.continues_step(EditKind::Typing, character);
         let insert_command = InsertCharacter::new(self, text, character);
         insert_command.apply(&mut self.cursor, text);
-        self.undo_stack.push(Box::new(insert_command));
+        self.push_command(
+            Box::new(insert_command),
+            merge,
+            Some((EditKind::Typing, character)),
+        );
     }
 
+    /// Insert the string as a single undo step, e.g. a paste
     pub fn insert_string(&mut self, text: &mut String, string: String) {
         self.redo_stack.clear();
 
@@ -275,7 +703,7 @@ impl EditboxState {
 
         let insert_command = InsertString::new(self, text, string.to_owned());
         insert_command.apply(&mut self.cursor, text);
-        self.undo_stack.push(Box::new(insert_command));
+        self.push_command(Box::new(insert_command), false, None);
     }
 
     pub fn delete_selected(&mut self, text: &mut String) {
@@ -284,48 +712,80 @@ impl EditboxState {
         if let Some(range) = self.selection {
             let delete_command = DeleteRange::new(text, range);
             delete_command.apply(&mut self.cursor, text);
-            self.undo_stack.push(Box::new(delete_command));
+            self.push_command(Box::new(delete_command), false, None);
         }
         self.selection = None;
     }
 
-    pub fn delete_next_character(&mut self, text: &mut String) {
+    /// Delete the character after the cursor, `merge` tells if it joins the last undo step
+    fn delete_character(&mut self, text: &mut String, kind: EditKind, merge: bool) {
         self.redo_stack.clear();
 
         if let Some(delete_command) = DeleteCharacter::new(self, text) {
+            let character = delete_command.data.chars().next().unwrap();
             delete_command.apply(&mut self.cursor, text);
-            self.undo_stack.push(Box::new(delete_command));
+            self.push_command(Box::new(delete_command), merge, Some((kind, character)));
         }
     }
 
+    pub fn delete_next_character(&mut self, text: &mut String) {
+        let merge = text[self.cursor as usize..]
+            .chars()
+            .next()
+            .map_or(false, |character| {
+                self.continues_step(EditKind::Delete, character)
+            });
+
+        self.delete_character(text, EditKind::Delete, merge);
+    }
+
     pub fn delete_current_character(&mut self, text: &mut String) {
         if self.cursor > 0 {
-            self.cursor -= 1;
-            self.delete_next_character(text);
+            let previous = prev_boundary(text, self.cursor as usize);
+            let character = text[previous..].chars().next().unwrap();
+            let merge = self.continues_step(EditKind::Backspace, character);
+
+            self.cursor = previous as u32;
+            self.delete_character(text, EditKind::Backspace, merge);
         }
     }
 
     pub fn move_cursor_next_word(&mut self, text: &str, shift: bool) {
-        let next_word = self.find_word_end(text, self.cursor + 1) + 1;
-        self.move_cursor(text, next_word as i32, shift);
+        let next = next_boundary(text, self.cursor as usize) as u32;
+        let next_word = next + self.find_word_end(text, next);
+        self.move_cursor_to(text, next_word, shift);
     }
 
     pub fn move_cursor_prev_word(&mut self, text: &str, shift: bool) {
-        if self.cursor > 1 {
-            let prev_word = self.find_word_begin(text, self.cursor - 1) + 1;
-            self.move_cursor(text, -(prev_word as i32), shift);
+        if self.cursor > 0 {
+            let prev = prev_boundary(text, self.cursor as usize) as u32;
+            let prev_word = prev - self.find_word_begin(text, prev);
+            self.move_cursor_to(text, prev_word, shift);
         }
     }
 
+    /// Move the cursor by `dx` characters
     pub fn move_cursor(&mut self, text: &str, dx: i32, shift: bool) {
-        let start_cursor = self.cursor;
-        let mut end_cursor = start_cursor;
+        let mut cursor = self.cursor as usize;
 
-        if self.cursor as i32 + dx <= text.len() as i32 && self.cursor as i32 + dx >= 0 {
-            end_cursor = (self.cursor as i32 + dx) as u32;
-            self.cursor = end_cursor;
+        for _ in 0..dx.abs() {
+            cursor = if dx > 0 {
+                next_boundary(text, cursor)
+            } else {
+                prev_boundary(text, cursor)
+            };
         }
 
+        self.move_cursor_to(text, cursor as u32, shift);
+    }
+
+    /// Move the cursor to the byte offset, extending the selection with `shift`
+    pub fn move_cursor_to(&mut self, text: &str, cursor: u32, shift: bool) {
+        let start_cursor = self.cursor;
+        let end_cursor = snap(text, cursor);
+
+        self.cursor = end_cursor;
+
         if shift == false {
             self.selection = None;
         }
@@ -339,19 +799,6 @@ impl EditboxState {
         }
     }
 
-    pub fn move_cursor_within_line(&mut self, text: &String, dx: i32, shift: bool) {
-        assert!(dx >= 0, "not implemented");
-
-        for _ in 0..dx {
-            if text.chars().nth(self.cursor as usize).unwrap_or('x') == '\n'
-                || self.cursor == text.len() as u32
-            {
-                break;
-            }
-            self.move_cursor(text, 1, shift);
-        }
-    }
-
     pub fn select_all(&mut self, text: &str) {
         self.selection = Some((0, text.len() as u32));
         self.click_state = ClickState::None;
@@ -381,6 +828,7 @@ impl EditboxState {
     }
 
     pub fn click_down(&mut self, time: f32, text: &str, cursor: u32) {
+        let cursor = snap(text, cursor);
         self.current_click = cursor;
 
         if self.last_click == self.current_click && time - self.last_click_time < DOUBLE_CLICK_TIME
@@ -419,6 +867,7 @@ impl EditboxState {
     }
 
     pub fn click_move(&mut self, text: &str, cursor: u32) {
+        let cursor = snap(text, cursor);
         self.cursor = cursor;
 
         if self.cursor != self.last_click {
@@ -480,20 +929,116 @@ impl EditboxState {
     }
 
     pub fn undo(&mut self, text: &mut String) {
-        let command = self.undo_stack.pop();
+        self.open_step = None;
 
-        if let Some(command) = command {
-            command.unapply(&mut self.cursor, text);
-            self.redo_stack.push(command);
+        if let Some(step) = self.undo_stack.pop() {
+            for command in step.iter().rev() {
+                command.unapply(&mut self.cursor, text);
+            }
+            self.redo_stack.push(step);
         }
     }
 
     pub fn redo(&mut self, text: &mut String) {
-        let command = self.redo_stack.pop();
+        self.open_step = None;
 
-        if let Some(command) = command {
-            command.apply(&mut self.cursor, text);
-            self.undo_stack.push(command);
+        if let Some(step) = self.redo_stack.pop() {
+            for command in &step {
+                command.apply(&mut self.cursor, text);
+            }
+            self.undo_stack.push(step);
         }
     }
+
+    pub fn history(&self) -> EditboxHistory {
+        let edits = |stack: &[Step]| {
+            stack
+                .iter()
+                .map(|step| step.iter().map(|command| command.edit()).collect())
+                .collect()
+        };
+
+        EditboxHistory {
+            undo: edits(&self.undo_stack),
+            redo: edits(&self.redo_stack),
+        }
+    }
+
+    /// Forget all the undo and redo steps
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.open_step = None;
+    }
+}
+
+/// Offset of the bracket closing the first one, skipping the nested pairs
+fn find_pair(
+    mut characters: impl Iterator<Item = (usize, char)>,
+    bracket: char,
+    pair: char,
+) -> Option<usize> {
+    let mut depth = 0;
+
+    characters
+        .find(|&(_, character)| {
+            if character == bracket {
+                depth += 1;
+            } else if character == pair {
+                depth -= 1;
+            }
+            depth == 0
+        })
+        .map(|(n, _)| n)
+}
+
+/// Marks drawn over the previous character, never separated from it by the cursor
+fn is_combining(character: char) -> bool {
+    matches!(character as u32,
+        0x0300..=0x036F // combining diacritical marks
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200D // zero width joiner
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F // variation selectors
+        | 0xFE20..=0xFE2F)
+}
+
+/// Byte offset of the next cursor position after `position`, or the text length
+fn next_boundary(text: &str, position: usize) -> usize {
+    let mut chars = text[position..].char_indices().skip(1);
+
+    chars
+        .find(|(_, character)| !is_combining(*character))
+        .map_or(text.len(), |(n, _)| position + n)
+}
+
+/// Byte offset of the previous cursor position before `position`, or 0
+fn prev_boundary(text: &str, position: usize) -> usize {
+    text[..position]
+        .char_indices()
+        .rev()
+        .find(|(_, character)| !is_combining(*character))
+        .map_or(0, |(n, _)| n)
+}
+
+/// Fingerprint of the text to notice the changes made outside of the editbox
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Closest cursor position at or before the byte offset
+fn snap(text: &str, position: u32) -> u32 {
+    let mut position = (position as usize).min(text.len());
+
+    while !text.is_char_boundary(position) {
+        position -= 1;
+    }
+    if position < text.len() && text[position..].starts_with(is_combining) {
+        position = prev_boundary(text, position);
+    }
+
+    position as u32
 }
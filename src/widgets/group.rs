@@ -62,8 +62,6 @@ impl Group {
 
         let parent = ui.get_active_window_context();
 
-        let parent_rect = parent.window.content_rect();
-
         parent.window.childs.push(self.id);
 
         let pos = parent.window.cursor.fit(
@@ -111,12 +109,10 @@ impl Group {
             }
         }
 
-	context.window.draw_commands.clip(parent_rect);
-
-	context.scroll_area();
+        context.scroll_area();
 
         let clip_rect = context.window.content_rect();
-        context.window.draw_commands.clip(clip_rect);
+        context.window.draw_commands.push_clip(clip_rect);
         context.window.draw_commands.draw_rect(
             rect,
             context
@@ -146,7 +142,7 @@ impl GroupToken {
     pub fn end(self, ui: &mut Ui) -> Drag {
         let context = ui.get_active_window_context();
 
-        context.window.draw_commands.clip(None);
+        context.window.draw_commands.pop_clip();
 
         if context.focused && self.draggable {
             if
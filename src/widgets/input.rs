@@ -1,14 +1,24 @@
 use crate::{
-    types::{Color, Vector2},
-    widgets::Editbox,
-    Id, Layout, Ui,
+    hash,
+    types::{Color, Rect, Vector2},
+    widgets::{Completer, Editbox, EditboxResponse},
+    Aligment, Id, Layout, Ui, VerticalAligment,
 };
 
+/// Whole text check, `Err` holds the message for the user
+type Validator<'a> = &'a dyn Fn(&str) -> Result<(), String>;
+
 pub struct InputText<'a> {
     id: Id,
     label: &'a str,
     size: Option<Vector2>,
     numbers: bool,
+    password: bool,
+    reveal_toggle: bool,
+    placeholder: Option<&'a str>,
+    max_length: Option<usize>,
+    validator: Option<Validator<'a>>,
+    completer: Option<&'a dyn Completer>,
 }
 
 #[deprecated(note = "Use InputText instead")]
@@ -21,15 +31,30 @@ impl<'a> InputText<'a> {
             size: None,
             label: "",
             numbers: false,
+            password: false,
+            reveal_toggle: false,
+            placeholder: None,
+            max_length: None,
+            validator: None,
+            completer: None,
         }
     }
 
-    pub fn label<'b>(self, label: &'b str) -> InputText<'b> {
+    pub fn label<'b>(self, label: &'b str) -> InputText<'b>
+    where
+        'a: 'b,
+    {
         InputText {
             id: self.id,
             size: self.size,
             label,
             numbers: self.numbers,
+            password: self.password,
+            reveal_toggle: self.reveal_toggle,
+            placeholder: self.placeholder,
+            max_length: self.max_length,
+            validator: self.validator,
+            completer: self.completer,
         }
     }
 
@@ -47,8 +72,55 @@ impl<'a> InputText<'a> {
         }
     }
 
-    pub fn ui(self, ui: &mut Ui, data: &mut String) {
-        let context = ui.get_active_window_context();
+    /// See `Editbox::placeholder`
+    pub fn placeholder(self, placeholder: &'a str) -> Self {
+        Self {
+            placeholder: Some(placeholder),
+            ..self
+        }
+    }
+
+    /// See `Editbox::max_length`
+    pub fn max_length(self, max_length: usize) -> Self {
+        Self {
+            max_length: Some(max_length),
+            ..self
+        }
+    }
+
+    /// Check the whole text after each frame's input.
+    /// The error is shown under the field, which gets the `Style::validation_error` border.
+    /// The row for the error is reserved while the text is valid too.
+    pub fn validate(self, validator: Validator<'a>) -> Self {
+        Self {
+            validator: Some(validator),
+            ..self
+        }
+    }
+
+    /// See `Editbox::completer`
+    pub fn completer(self, completer: &'a dyn Completer) -> Self {
+        Self {
+            completer: Some(completer),
+            ..self
+        }
+    }
+
+    /// Mask the input, see `Editbox::password`
+    pub fn password(self, password: bool) -> Self {
+        Self { password, ..self }
+    }
+
+    /// Button to show and hide the password, only for the `password` inputs
+    pub fn reveal_toggle(self) -> Self {
+        Self {
+            reveal_toggle: true,
+            ..self
+        }
+    }
+
+    pub fn ui(self, ui: &mut Ui, data: &mut String) -> EditboxResponse {
+        let mut context = ui.get_active_window_context();
 
         let size = self.size.unwrap_or_else(|| {
             Vector2::new(
@@ -65,17 +137,87 @@ impl<'a> InputText<'a> {
         } else {
             size.x / 2.
         };
-        let mut editbox = Editbox::new(self.id, Vector2::new(editbox_area_w, size.y))
+
+        let mut masked = self.password;
+        let mut editbox_w = editbox_area_w;
+        if self.password && self.reveal_toggle {
+            // square button at the right end of the editbox area
+            let toggle_rect = Rect::new(pos.x + editbox_area_w - size.y, pos.y, size.y, size.y);
+            editbox_w -= size.y + 2.;
+
+            let (hovered, clicked) = context.register_click_intention(toggle_rect);
+            let revealed = context
+                .storage_u32
+                .entry(hash!(self.id, "reveal"))
+                .or_insert(0);
+            if context.focused && clicked {
+                *revealed ^= 1;
+            }
+            masked = *revealed == 0;
+
+            context.window.draw_commands.draw_rect(
+                toggle_rect,
+                None,
+                context.global_style.button_background(
+                    context.focused,
+                    hovered,
+                    hovered && context.input.is_mouse_down(),
+                ),
+            );
+            context.window.draw_commands.draw_label_aligned(
+                if masked { "*" } else { "a" },
+                toggle_rect,
+                Aligment::Center,
+                VerticalAligment::Center,
+                context.global_style.text(context.focused),
+            );
+        }
+
+        let mut editbox = Editbox::new(self.id, Vector2::new(editbox_w, size.y))
             .position(pos)
-            .multiline(false);
+            .multiline(false)
+            .password(masked);
+        if let Some(placeholder) = self.placeholder {
+            editbox = editbox.placeholder(placeholder);
+        }
+        if let Some(max_length) = self.max_length {
+            editbox = editbox.max_length(max_length);
+        }
+        if let Some(completer) = self.completer {
+            editbox = editbox.completer(completer);
+        }
         if self.numbers {
             editbox = editbox
                 .filter(&|character| character.is_digit(10) || character == '.' || character == '-')
         }
-        editbox.ui(ui, data);
+        let response = editbox.ui(ui, data);
 
         let context = ui.get_active_window_context();
 
+        if let Some(validator) = self.validator {
+            // the error row is there for a valid text too, so the widgets below do not jump
+            let error_size = Vector2::new(
+                editbox_w,
+                context.window.draw_commands.line_height() + context.global_style.margin * 2.,
+            );
+            let error_pos = context.window.cursor.fit(error_size, Layout::Vertical);
+
+            if let Err(error) = validator(data) {
+                let color = context.global_style.validation_error(context.focused);
+
+                context.window.draw_commands.draw_rect(
+                    Rect::new(pos.x, pos.y, editbox_w, size.y),
+                    color,
+                    None,
+                );
+                context.window.draw_commands.draw_label(
+                    &error,
+                    error_pos + Vector2::new(0., context.global_style.margin),
+                    color,
+                );
+            }
+        }
+
         if self.label.is_empty() == false {
             context.window.draw_commands.draw_label(
                 self.label,
@@ -83,16 +225,18 @@ impl<'a> InputText<'a> {
                 Color::from_rgba(0, 0, 0, 255),
             );
         }
+
+        response
     }
 }
 
 impl Ui {
     #[deprecated(note = "Use input_text instead")]
     pub fn input_field(&mut self, id: Id, label: &str, data: &mut String) {
-        InputText::new(id).label(label).ui(self, data)
+        InputText::new(id).label(label).ui(self, data);
     }
 
-    pub fn input_text(&mut self, id: Id, label: &str, data: &mut String) {
+    pub fn input_text(&mut self, id: Id, label: &str, data: &mut String) -> EditboxResponse {
         InputText::new(id).label(label).ui(self, data)
     }
 }
@@ -1,10 +1,25 @@
-use crate::{types::Vector2, Layout, Ui};
+use crate::{
+    types::{Rect, Vector2},
+    Aligment, Layout, Ui, VerticalAligment,
+};
 
 use std::borrow::Cow;
 
+#[derive(Debug, Clone, Copy)]
+enum Wrap {
+    None,
+    /// Wrap to the width left in the window
+    Available,
+    Width(f32),
+}
+
 pub struct Label<'a> {
     position: Option<Vector2>,
     multiline: Option<f32>,
+    wrap: Wrap,
+    size: Option<Vector2>,
+    aligment: Aligment,
+    vertical_aligment: VerticalAligment,
     label: Cow<'a, str>,
 }
 
@@ -16,6 +31,10 @@ impl<'a> Label<'a> {
         Label {
             position: None,
             multiline: None,
+            wrap: Wrap::None,
+            size: None,
+            aligment: Aligment::Left,
+            vertical_aligment: VerticalAligment::Top,
             label: label.into(),
         }
     }
@@ -27,6 +46,43 @@ impl<'a> Label<'a> {
         }
     }
 
+    /// Word wrap the text to the window's width.
+    /// Line height is taken from `multiline` or from the active font.
+    pub fn wrap(self) -> Self {
+        Label {
+            wrap: Wrap::Available,
+            ..self
+        }
+    }
+
+    /// Word wrap the text to the given width
+    pub fn wrap_width(self, width: f32) -> Self {
+        Label {
+            wrap: Wrap::Width(width),
+            ..self
+        }
+    }
+
+    /// Fixed size instead of the size of the text.
+    /// Single line labels not fitting the size are truncated and shown in a tooltip on hover.
+    pub fn size(self, size: Vector2) -> Self {
+        Label {
+            size: Some(size),
+            ..self
+        }
+    }
+
+    pub fn aligment(self, aligment: Aligment) -> Self {
+        Label { aligment, ..self }
+    }
+
+    pub fn vertical_aligment(self, vertical_aligment: VerticalAligment) -> Self {
+        Label {
+            vertical_aligment,
+            ..self
+        }
+    }
+
     pub fn position<P: Into<Option<Vector2>>>(self, position: P) -> Self {
         let position = position.into();
 
@@ -36,33 +92,86 @@ impl<'a> Label<'a> {
     pub fn ui(self, ui: &mut Ui) {
         let context = ui.get_active_window_context();
 
-        let mut size = context
-            .window
-            .draw_commands
-            .label_size(&self.label, self.multiline);
+        let wrap_width = match self.wrap {
+            Wrap::None => None,
+            Wrap::Available => {
+                let cursor = &context.window.cursor;
+                Some(context.window.content_rect().w - cursor.margin * 2. - cursor.ident)
+            }
+            Wrap::Width(width) => Some(width),
+        };
 
-        size.y += context.global_style.margin * 2.;
+        let draw_commands = &context.window.draw_commands;
+        let (lines, line_height) = match (wrap_width, self.multiline) {
+            (Some(width), line_height) => (
+                draw_commands.wrap_text(&self.label, width),
+                Some(line_height.unwrap_or_else(|| draw_commands.line_height())),
+            ),
+            (None, Some(line_height)) => (self.label.split('\n').collect(), Some(line_height)),
+            (None, None) => (vec![&*self.label], None),
+        };
+
+        let text_size = match line_height {
+            Some(line_height) => Vector2::new(
+                lines.iter().fold(0.0f32, |width, line| {
+                    width.max(draw_commands.label_size(line, None).x)
+                }),
+                line_height * lines.len() as f32,
+            ),
+            None => draw_commands.label_size(&self.label, None),
+        };
+
+        let margin = context.global_style.margin;
+        let size = self
+            .size
+            .unwrap_or(text_size + Vector2::new(0., margin * 2.));
 
         let color = context.global_style.text(context.focused);
         let pos = context
             .window
             .cursor
-            .fit(size, self.position.map_or(Layout::Vertical, Layout::Free))
-            + Vector2::new(0., context.global_style.margin);
+            .fit(size, self.position.map_or(Layout::Vertical, Layout::Free));
+        let rect = Rect::new(pos.x, pos.y + margin, size.x, size.y - margin * 2.);
+
+        let mut truncated = false;
+        if self.size.is_some() && line_height.is_none() {
+            truncated = context.window.draw_commands.draw_label_aligned(
+                &self.label,
+                rect,
+                self.aligment,
+                self.vertical_aligment,
+                color,
+            );
+        } else {
+            let y = match self.vertical_aligment {
+                VerticalAligment::Top => 0.,
+                VerticalAligment::Center => (rect.h - text_size.y) / 2.,
+                VerticalAligment::Bottom => rect.h - text_size.y,
+            };
+            let line_height = line_height.unwrap_or(0.);
+
+            for (n, line) in lines.iter().enumerate() {
+                let width = context.window.draw_commands.label_size(line, None).x;
+                let x = match self.aligment {
+                    Aligment::Left => 0.,
+                    Aligment::Center => (rect.w - width) / 2.,
+                    Aligment::Right => rect.w - width,
+                };
 
-        if let Some(line_height) = self.multiline {
-            for (n, line) in self.label.split('\n').enumerate() {
                 context.window.draw_commands.draw_label(
                     line,
-                    pos + Vector2::new(0., n as f32 * line_height),
+                    Vector2::new(
+                        rect.x + x.round(),
+                        rect.y + y.round() + n as f32 * line_height,
+                    ),
                     color,
                 )
             }
-        } else {
-            context
-                .window
-                .draw_commands
-                .draw_label(&*self.label, pos, color)
+        }
+
+        let hovered = context.input.window_active && rect.contains(context.input.mouse_position);
+        if hovered && truncated {
+            ui.tooltip(&self.label);
         }
     }
 }
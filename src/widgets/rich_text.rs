@@ -0,0 +1,302 @@
+use crate::{
+    draw_command::CommandsList,
+    types::{Color, Rect, Vector2},
+    FontId, Layout, Ui,
+};
+
+use std::borrow::Cow;
+
+/// Fragment of a `RichText` with its own style.
+/// Unset color and font are taken from the style and the active font.
+#[derive(Debug, Clone)]
+pub struct Span<'a> {
+    text: Cow<'a, str>,
+    color: Option<Color>,
+    background: Option<Color>,
+    font: Option<FontId>,
+    underline: bool,
+}
+
+impl<'a> Span<'a> {
+    pub fn new<S>(text: S) -> Span<'a>
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        Span {
+            text: text.into(),
+            color: None,
+            background: None,
+            font: None,
+            underline: false,
+        }
+    }
+
+    pub fn color(self, color: Color) -> Self {
+        Span {
+            color: Some(color),
+            ..self
+        }
+    }
+
+    /// Highlight the span with a filled rect behind the text
+    pub fn background(self, background: Color) -> Self {
+        Span {
+            background: Some(background),
+            ..self
+        }
+    }
+
+    pub fn font(self, font: FontId) -> Self {
+        Span {
+            font: Some(font),
+            ..self
+        }
+    }
+
+    pub fn underline(self) -> Self {
+        Span {
+            underline: true,
+            ..self
+        }
+    }
+}
+
+/// Label made of differently styled spans.
+/// Spans are drawn one after another, `\n` in the span's text starts a new line.
+///
+/// Can be built span by span:
+/// ```skip
+/// RichText::new()
+///     .span(Span::new("[ERROR]").color(Color::from_rgb(200, 0, 0)))
+///     .text(" file not found")
+///     .ui(ui);
+/// ```
+/// or from a markup, see `RichText::markup`.
+pub struct RichText<'a> {
+    position: Option<Vector2>,
+    spans: Vec<Span<'a>>,
+}
+
+impl<'a> RichText<'a> {
+    pub fn new() -> RichText<'a> {
+        RichText {
+            position: None,
+            spans: vec![],
+        }
+    }
+
+    /// Parse a markup with `[tag]text[/]` style tags:
+    ///
+    /// - `[color=#rrggbb]` or `[color=#rrggbbaa]` - text color
+    /// - `[bg=#rrggbb]` or `[bg=#rrggbbaa]` - background highlight
+    /// - `[u]` - underline
+    ///
+    /// Tags may be nested, `[/]` closes the last opened tag and `[[` is a literal `[`.
+    /// Anything else in square brackets is kept as is.
+    pub fn markup(markup: &str) -> RichText<'static> {
+        let mut spans = vec![];
+        let mut stack: Vec<Span<'static>> = vec![];
+        let mut text = String::new();
+
+        let current = |stack: &[Span<'static>]| {
+            stack
+                .last()
+                .cloned()
+                .unwrap_or_else(|| Span::new(String::new()))
+        };
+        let flush = |text: &mut String, stack: &[Span<'static>], spans: &mut Vec<Span<'static>>| {
+            if !text.is_empty() {
+                spans.push(Span {
+                    text: Cow::Owned(std::mem::take(text)),
+                    ..current(stack)
+                });
+            }
+        };
+
+        let mut rest = markup;
+        while let Some(start) = rest.find('[') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            if rest.starts_with("[[") {
+                text.push('[');
+                rest = &rest[2..];
+                continue;
+            }
+
+            let tag = rest.find(']').map(|end| &rest[1..end]);
+            let span = current(&stack);
+            let opened = match tag {
+                Some("u") => Some(span.underline()),
+                Some(tag) if tag.starts_with("color=") => {
+                    parse_color(&tag["color=".len()..]).map(|color| span.color(color))
+                }
+                Some(tag) if tag.starts_with("bg=") => {
+                    parse_color(&tag["bg=".len()..]).map(|color| span.background(color))
+                }
+                _ => None,
+            };
+
+            match (tag, opened) {
+                (Some(tag), Some(span)) => {
+                    flush(&mut text, &stack, &mut spans);
+                    stack.push(span);
+                    rest = &rest[tag.len() + 2..];
+                }
+                (Some("/"), None) => {
+                    flush(&mut text, &stack, &mut spans);
+                    stack.pop();
+                    rest = &rest[3..];
+                }
+                _ => {
+                    text.push('[');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        text.push_str(rest);
+        flush(&mut text, &stack, &mut spans);
+
+        RichText {
+            position: None,
+            spans,
+        }
+    }
+
+    pub fn span(mut self, span: Span<'a>) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    /// Span with the default style
+    pub fn text<S>(self, text: S) -> Self
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        self.span(Span::new(text))
+    }
+
+    pub fn position<P: Into<Option<Vector2>>>(self, position: P) -> Self {
+        let position = position.into();
+
+        RichText { position, ..self }
+    }
+
+    pub fn ui(self, ui: &mut Ui) {
+        let context = ui.get_active_window_context();
+        let draw_commands = &mut context.window.draw_commands;
+
+        // split spans into lines of (span, text) runs
+        let mut lines = vec![vec![]];
+        for span in &self.spans {
+            for (n, text) in span.text.split('\n').enumerate() {
+                if n != 0 {
+                    lines.push(vec![]);
+                }
+                if !text.is_empty() {
+                    lines.last_mut().unwrap().push((span, text));
+                }
+            }
+        }
+
+        let default_line_height = draw_commands.line_height();
+        let mut size = Vector2::new(0., 0.);
+        // (height, baseline) of each line, spans of all the sizes share the baseline
+        let mut line_metrics = vec![];
+        for line in &lines {
+            let mut width = 0.;
+            let mut baseline = 0.;
+            let mut descent = 0.;
+            for (span, text) in line {
+                with_font(draw_commands, span, |draw_commands| {
+                    width += draw_commands.label_size(text, None).x;
+                    baseline = f32::max(baseline, draw_commands.baseline());
+                    descent = f32::max(
+                        descent,
+                        draw_commands.line_height() - draw_commands.baseline(),
+                    );
+                });
+            }
+            let height = if line.is_empty() {
+                default_line_height
+            } else {
+                baseline + descent
+            };
+            size.x = size.x.max(width);
+            size.y += height;
+            line_metrics.push((height, baseline));
+        }
+
+        size.y += context.global_style.margin * 2.;
+
+        let text_color = context.global_style.text(context.focused);
+        let mut pos = context
+            .window
+            .cursor
+            .fit(size, self.position.map_or(Layout::Vertical, Layout::Free))
+            + Vector2::new(0., context.global_style.margin);
+        let draw_commands = &mut context.window.draw_commands;
+
+        for (line, (line_height, baseline)) in lines.iter().zip(line_metrics) {
+            let mut x = pos.x;
+            for (span, text) in line {
+                with_font(draw_commands, span, |draw_commands| {
+                    let width = draw_commands.label_size(text, None).x;
+                    let y = pos.y + baseline - draw_commands.baseline();
+
+                    if let Some(background) = span.background {
+                        let rect = Rect::new(x, y, width, draw_commands.line_height());
+                        draw_commands.draw_rect(rect, None, background);
+                    }
+                    draw_commands.draw_label(
+                        text,
+                        Vector2::new(x, y),
+                        span.color.unwrap_or(text_color),
+                    );
+                    if span.underline {
+                        let rect = Rect::new(x, y + draw_commands.baseline() + 1., width, 1.);
+                        draw_commands.draw_rect(rect, None, span.color.unwrap_or(text_color));
+                    }
+
+                    x += width;
+                });
+            }
+            pos.y += line_height;
+        }
+    }
+}
+
+impl Default for RichText<'_> {
+    fn default() -> Self {
+        RichText::new()
+    }
+}
+
+/// Run `f` with the span's font active
+fn with_font<F: FnMut(&mut CommandsList)>(draw_commands: &mut CommandsList, span: &Span, mut f: F) {
+    if let Some(font) = span.font {
+        draw_commands.push_font(font);
+    }
+    f(draw_commands);
+    if span.font.is_some() {
+        draw_commands.pop_font();
+    }
+}
+
+fn parse_color(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#')?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+
+    match hex.len() {
+        6 => Some(Color::from_rgb_u32(value)),
+        8 => Some(Color::from_rgba_u32(value)),
+        _ => None,
+    }
+}
+
+impl Ui {
+    /// Label from a markup, see `RichText::markup`
+    pub fn rich_text<P: Into<Option<Vector2>>>(&mut self, position: P, markup: &str) {
+        RichText::markup(markup).position(position).ui(self)
+    }
+}
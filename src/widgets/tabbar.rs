@@ -1,13 +1,14 @@
 use crate::{
-    draw_command::Aligment,
     types::{Color, Rect, Vector2},
-    Id, Layout, Ui,
+    Aligment, Id, Layout, Ui, VerticalAligment,
 };
 
 pub struct Tabbar<'a> {
     id: Id,
     size: Vector2,
     selected_tab: Option<usize>,
+    aligment: Aligment,
+    vertical_aligment: VerticalAligment,
     tabs: &'a [&'a str],
 }
 
@@ -18,6 +19,8 @@ impl Tabbar<'_> {
             size,
             tabs,
             selected_tab: None,
+            aligment: Aligment::Center,
+            vertical_aligment: VerticalAligment::Top,
         }
     }
 
@@ -28,6 +31,18 @@ impl Tabbar<'_> {
         }
     }
 
+    /// Tab titles not fitting the tab are truncated and shown in a tooltip on hover
+    pub fn aligment(self, aligment: Aligment) -> Self {
+        Tabbar { aligment, ..self }
+    }
+
+    pub fn vertical_aligment(self, vertical_aligment: VerticalAligment) -> Self {
+        Tabbar {
+            vertical_aligment,
+            ..self
+        }
+    }
+
     pub fn ui(self, ui: &mut Ui) -> u32 {
         let context = ui.get_active_window_context();
 
@@ -42,6 +57,7 @@ impl Tabbar<'_> {
             *selected_mut
         };
 
+        let mut truncated_hovered = None;
         for (n, label) in self.tabs.iter().enumerate() {
             let rect = Rect::new(
                 pos.x + width * n as f32 + 1.,
@@ -66,34 +82,40 @@ impl Tabbar<'_> {
                 ),
             );
 
-            let text_width = context.window.draw_commands.label_size(label, None).x;
-            context.window.draw_commands.draw_label(
+            // the same padding above and below the title
+            let text_top = context.global_style.margin_button + 2.;
+            let truncated = context.window.draw_commands.draw_label_aligned(
                 label,
-                pos + Vector2::new(
-                    width * n as f32 + (width - text_width) / 2.,
-                    context.global_style.margin_button + 2.,
-                ),
-                (
-                    if selected {
-                        Color::new(1., 1., 1., 1.)
-                    } else {
-                        context.global_style.text(context.focused)
-                    },
-                    Aligment::Center,
+                Rect::new(
+                    rect.x,
+                    rect.y + text_top,
+                    rect.w,
+                    (rect.h - text_top * 2.).max(0.),
                 ),
+                self.aligment,
+                self.vertical_aligment,
+                if selected {
+                    Color::new(1., 1., 1., 1.)
+                } else {
+                    context.global_style.text(context.focused)
+                },
             );
+            if hovered && truncated {
+                truncated_hovered = Some(*label);
+            }
         }
-        context.storage_u32[&self.id]
+
+        let selected = context.storage_u32[&self.id];
+
+        if let Some(label) = truncated_hovered {
+            ui.tooltip(label);
+        }
+
+        selected
     }
 }
-
 impl Ui {
-    pub fn tabbar<'a>(
-        &mut self,
-        id: Id,
-        size: Vector2,
-        tabs: &'a [&'a str],
-    ) -> u32 {
+    pub fn tabbar<'a>(&mut self, id: Id, size: Vector2, tabs: &'a [&'a str]) -> u32 {
         Tabbar::new(id, size, tabs).ui(self)
     }
 }
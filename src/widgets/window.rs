@@ -13,6 +13,7 @@ pub struct Window {
     enabled: bool,
     movable: bool,
     titlebar: bool,
+    background_alpha: f32,
     label: Option<String>,
 }
 
@@ -26,6 +27,7 @@ impl Window {
             enabled: true,
             movable: true,
             titlebar: true,
+            background_alpha: 1.,
             label: None,
         }
     }
@@ -52,6 +54,14 @@ impl Window {
         Window { titlebar, ..self }
     }
 
+    /// Multiplier for the style's background alpha, 0.0 makes the window background fully transparent
+    pub fn background_alpha(self, background_alpha: f32) -> Window {
+        Window {
+            background_alpha,
+            ..self
+        }
+    }
+
     pub fn enabled(self, enabled: bool) -> Window {
         Window { enabled, ..self }
     }
@@ -94,7 +104,7 @@ impl Window {
         let clip_rect = context.window.content_rect();
         context.scroll_area();
 
-        context.window.draw_commands.clip(clip_rect);
+        context.window.draw_commands.push_clip(clip_rect);
 
         WindowToken
     }
@@ -125,13 +135,28 @@ impl Window {
         let position = context.window.position;
         let size = context.window.size;
 
+        let mut background = style.background(focused);
+        background.a *= self.background_alpha;
+
+        context.draw_shadow();
         context.window.draw_commands.draw_rect(
             Rect::new(position.x, position.y, size.x, size.y),
             style.window_border(focused),
-            style.background(focused),
+            background,
         );
 
         if self.titlebar {
+            if let Some(gradient) = style.title_background(focused) {
+                context.window.draw_commands.draw_rect_gradient(
+                    Rect::new(
+                        position.x + 1.,
+                        position.y + 1.,
+                        size.x - 2.,
+                        style.title_height - 1.,
+                    ),
+                    gradient,
+                );
+            }
             if let Some(label) = &self.label {
                 context.window.draw_commands.draw_label(
                     &label,
@@ -154,7 +179,7 @@ pub struct WindowToken;
 impl WindowToken {
     pub fn end(self, ui: &mut Ui) -> bool {
         let context = ui.get_active_window_context();
-        context.window.draw_commands.clip(None);
+        context.window.draw_commands.pop_clip();
 
         let opened = context.window.want_close == false;
 
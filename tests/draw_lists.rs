@@ -0,0 +1,217 @@
+use megaui::{hash, widgets::Window, Color, CustomDraw, Rect, Ui, Vector2};
+
+fn render(ui: &mut Ui, f: impl Fn(&mut Ui)) -> Vec<megaui::DrawList> {
+    for _ in 0..2 {
+        ui.new_frame(1. / 60.);
+        f(ui);
+    }
+
+    let mut draw_lists = vec![];
+    ui.render(&mut draw_lists);
+    draw_lists
+}
+
+#[test]
+fn custom_draw_keeps_order_and_clipping() {
+    let mut ui = Ui::new();
+
+    let draw_lists = render(&mut ui, |ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(100., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                ui.label(None, "before");
+                ui.canvas().custom(Rect::new(50., 50., 100., 100.), 42);
+                ui.label(None, "after");
+            });
+    });
+
+    let custom = draw_lists
+        .iter()
+        .position(|draw_list| draw_list.custom.is_some())
+        .unwrap();
+
+    assert_eq!(
+        draw_lists[custom].custom,
+        Some(CustomDraw {
+            id: 42,
+            rect: Rect::new(50., 50., 100., 100.)
+        })
+    );
+    assert!(draw_lists[custom].vertices.is_empty());
+    assert_eq!(
+        draw_lists[custom].clipping_zone,
+        Some(Rect::new(10., 10., 100., 100.))
+    );
+
+    // the window background is below and the second label is above
+    assert!(draw_lists[..custom]
+        .iter()
+        .any(|list| !list.vertices.is_empty()));
+    assert!(draw_lists[custom + 1..]
+        .iter()
+        .any(|list| !list.vertices.is_empty()));
+}
+
+#[test]
+fn full_draw_list_continues_in_a_new_one() {
+    let mut ui = Ui::new();
+    let color = Color::from_rgb(1, 2, 3);
+
+    let draw_lists = render(&mut ui, |ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(100., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                for _ in 0..20000 {
+                    ui.canvas().rect(Rect::new(20., 20., 10., 10.), None, color);
+                }
+            });
+    });
+
+    let quads: Vec<_> = draw_lists
+        .iter()
+        .filter(|list| {
+            list.vertices
+                .iter()
+                .any(|vertex| vertex.color == [color.r, color.g, color.b, color.a])
+        })
+        .collect();
+
+    // u16 indices address 65536 vertices: 16384 quads, the next one starts a new list
+    assert_eq!(quads.len(), 2);
+    assert_eq!(quads[0].vertices.len(), 16384 * 4);
+    assert_eq!(quads[0].indices.len(), 16384 * 6);
+    assert_eq!(quads[1].vertices.len(), (20000 - 16384) * 4);
+    assert_eq!(quads[1].clipping_zone, quads[0].clipping_zone);
+
+    // the whole window is still a handful of draw calls
+    assert!(draw_lists.len() <= 6, "{} draw lists", draw_lists.len());
+
+    for list in &draw_lists {
+        assert!(list.vertices.len() <= u16::MAX as usize + 1);
+        assert!(list
+            .indices
+            .iter()
+            .all(|&index| (index as usize) < list.vertices.len()));
+    }
+}
+
+#[test]
+fn invalid_font_data() {
+    let mut ui = Ui::new();
+
+    assert!(ui.load_font(&[0, 1, 2, 3], 13).is_err());
+}
+
+#[test]
+fn new_glyphs_update_font_atlas() {
+    let mut ui = Ui::new();
+
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+    let revision = ui.font_atlas_revision();
+
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+    assert_eq!(ui.font_atlas_revision(), revision);
+
+    render(&mut ui, |ui| ui.label(None, "ñ"));
+    assert_ne!(ui.font_atlas_revision(), revision);
+}
+
+#[test]
+fn new_glyphs_are_copied_into_the_same_atlas() {
+    let mut ui = Ui::new();
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+    let before = ui.font_atlas.texture.data.clone();
+    let data = ui.font_atlas.texture.data.as_ptr();
+
+    render(&mut ui, |ui| ui.label(None, "ñé"));
+    assert_eq!(ui.font_atlas.texture.data.as_ptr(), data);
+    assert_ne!(ui.font_atlas.texture.data, before);
+
+    // an atlas still in use elsewhere gets replaced with a full copy instead
+    let mut shared = Ui::new();
+    render(&mut shared, |ui| ui.label(None, "ascii"));
+    let previous = shared.font_atlas.clone();
+    render(&mut shared, |ui| ui.label(None, "ñé"));
+
+    assert_eq!(previous.texture.data, before);
+    assert_eq!(shared.font_atlas.texture.data, ui.font_atlas.texture.data);
+}
+
+#[test]
+fn fallback_font_starts_a_new_atlas() {
+    let font = include_bytes!("../assets/ProggyClean.ttf");
+    let mut fresh = Ui::new();
+    render(&mut fresh, |ui| ui.label(None, "ascii"));
+
+    let mut ui = Ui::new();
+    render(&mut ui, |ui| ui.label(None, "ñéàü"));
+    let fallback = ui.load_font(&font[..], 13).unwrap();
+    ui.add_fallback_font(fallback);
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+
+    // the glyphs rasterized before the fallback do not take the space anymore
+    assert_eq!(ui.font_atlas.texture.data, fresh.font_atlas.texture.data);
+}
+
+#[test]
+fn fallback_font_is_added_on_the_next_frame() {
+    let font = include_bytes!("../assets/ProggyClean.ttf");
+    let mut ui = Ui::new();
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+    let revision = ui.font_atlas_revision();
+
+    ui.new_frame(1. / 60.);
+    ui.label(None, "ascii");
+    let fallback = ui.load_font(&font[..], 13).unwrap();
+    ui.add_fallback_font(fallback);
+    let mut draw_lists = vec![];
+    ui.render(&mut draw_lists);
+
+    // the label was recorded with the glyphs of the current atlas
+    assert_eq!(ui.font_atlas_revision(), revision);
+
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+    assert_ne!(ui.font_atlas_revision(), revision);
+}
+
+#[test]
+fn scale_changes_on_the_next_frame() {
+    let mut ui = Ui::new();
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+    let revision = ui.font_atlas_revision();
+
+    ui.new_frame(1. / 60.);
+    ui.label(None, "ascii");
+    ui.set_scale(2.);
+    let mut draw_lists = vec![];
+    ui.render(&mut draw_lists);
+
+    // the label was recorded with the glyphs of the current atlas
+    assert_eq!(ui.scale(), 1.);
+    assert_eq!(ui.font_atlas_revision(), revision);
+
+    render(&mut ui, |ui| ui.label(None, "ascii"));
+    assert_eq!(ui.scale(), 2.);
+    assert_ne!(ui.font_atlas_revision(), revision);
+}
+
+#[test]
+fn measure_text() {
+    let ui = Ui::new();
+
+    let metrics = ui.measure_text("ab\ncdé");
+    let advance = metrics.lines[0].carets[1];
+
+    assert_eq!(metrics.lines.len(), 2);
+    assert_eq!(metrics.lines[1].range, 3..7);
+    assert_eq!(metrics.lines[1].carets.len(), 4);
+    assert_eq!(metrics.lines[1].width, advance * 3.);
+    assert_eq!(metrics.size.x, advance * 3.);
+    assert_eq!(metrics.size.y, metrics.lines[0].height * 2.);
+
+    // caret positions and hit testing agree on multibyte characters
+    let caret = metrics.caret(7);
+    assert_eq!((caret.x, caret.y), (advance * 3., metrics.lines[1].y));
+    assert_eq!(metrics.index_at(metrics.caret(5)), 5);
+    assert_eq!(metrics.index_at(Vector2::new(1000., 0.)), 2);
+}
@@ -0,0 +1,545 @@
+use megaui::{
+    hash,
+    snapshot::{Event, Harness},
+    widgets::{
+        Completer, Editbox, EditboxResponse, Highlighter, InputText, SyntaxHighlighter, TextEdit,
+        Window,
+    },
+    ClipboardObject, Color, Id,
+    KeyCode::{self, *},
+    Ui, Vector2,
+};
+
+/// Focused editbox driven with the input events
+struct Editor {
+    harness: Harness,
+    id: Id,
+    text: String,
+    configure: fn(Editbox<'static>) -> Editbox<'static>,
+    /// All the events since the last `events` call
+    events: EditboxResponse,
+}
+
+impl Editor {
+    /// Focus an empty editbox and type the text in
+    fn new(text: &str) -> Editor {
+        Editor::with(text, |editbox| editbox)
+    }
+
+    fn with(text: &str, configure: fn(Editbox<'static>) -> Editbox<'static>) -> Editor {
+        let mut editor = Editor {
+            harness: Harness::new(200, 80),
+            id: hash!(),
+            text: String::new(),
+            configure,
+            events: EditboxResponse::default(),
+        };
+        let click = Vector2::new(30., 30.);
+
+        editor.frame(&[]);
+        editor.frame(&[Event::MouseDown(click)]);
+        editor.frame(&[Event::MouseUp(click)]);
+        editor.frame(&text.chars().map(Event::Char).collect::<Vec<_>>());
+
+        editor
+    }
+
+    fn frame(&mut self, events: &[Event]) {
+        let (id, text, configure) = (self.id, &mut self.text, self.configure);
+        let mut response = EditboxResponse::default();
+
+        self.harness.frame(events, |ui: &mut Ui| {
+            Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 60.))
+                .titlebar(false)
+                .ui(ui, |ui| {
+                    response = configure(Editbox::new(id, Vector2::new(150., 40.))).ui(ui, text);
+                });
+        });
+
+        let events = &mut self.events;
+        events.edited |= response.edited;
+        events.submitted |= response.submitted;
+        events.cancelled |= response.cancelled;
+        events.gained_focus |= response.gained_focus;
+        events.lost_focus |= response.lost_focus;
+    }
+
+    fn events(&mut self) -> EditboxResponse {
+        std::mem::take(&mut self.events)
+    }
+
+    fn key(&mut self, key: KeyCode, shift: bool, ctrl: bool) -> &mut Self {
+        self.frame(&[Event::KeyWithModifiers(key, shift, ctrl)]);
+        // a key held for more than one frame is a repeat, release it
+        self.frame(&[]);
+        self
+    }
+
+    fn press(&mut self, key: KeyCode) -> &mut Self {
+        self.key(key, false, false)
+    }
+
+    fn shift(&mut self, key: KeyCode) -> &mut Self {
+        self.key(key, true, false)
+    }
+
+    fn ctrl(&mut self, key: KeyCode) -> &mut Self {
+        self.key(key, false, true)
+    }
+
+    fn type_text(&mut self, text: &str) -> &mut Self {
+        self.frame(&text.chars().map(Event::Char).collect::<Vec<_>>());
+        self
+    }
+}
+
+#[test]
+fn insert_and_delete_multibyte() {
+    let mut editor = Editor::new("héllo wörld");
+    assert_eq!(editor.text, "héllo wörld");
+
+    editor.press(Left).press(Left).press(Left).type_text("X");
+    assert_eq!(editor.text, "héllo wöXrld");
+
+    editor.press(Backspace).press(Backspace);
+    assert_eq!(editor.text, "héllo wrld");
+
+    editor.press(Delete);
+    assert_eq!(editor.text, "héllo wld");
+}
+
+#[test]
+fn combining_marks_stay_with_their_character() {
+    let mut editor = Editor::new("e\u{301}x");
+
+    editor.press(Left).press(Left).type_text(">");
+    assert_eq!(editor.text, ">e\u{301}x");
+
+    editor.press(Delete);
+    assert_eq!(editor.text, ">x");
+}
+
+#[test]
+fn selection_replaces_multibyte_text() {
+    let mut editor = Editor::new("日本語 text");
+
+    editor.press(Home).shift(Right).shift(Right).type_text("ü");
+    assert_eq!(editor.text, "ü語 text");
+
+    editor.ctrl(A).type_text("🦀");
+    assert_eq!(editor.text, "🦀");
+}
+
+#[test]
+fn word_movement() {
+    let mut editor = Editor::new("über straße");
+
+    editor.ctrl(Left).type_text("große ");
+    assert_eq!(editor.text, "über große straße");
+
+    editor.press(Home).ctrl(Right).type_text("!");
+    assert_eq!(editor.text, "über !große straße");
+}
+
+#[test]
+fn vertical_movement_keeps_the_column() {
+    let mut editor = Editor::new("äää");
+    editor.press(Enter).type_text("b");
+
+    editor.press(Up).type_text("X");
+    assert_eq!(editor.text, "äXää\nb");
+
+    editor.press(Down).type_text("Y");
+    assert_eq!(editor.text, "äXää\nbY");
+}
+
+#[test]
+fn vertical_movement_follows_wrapped_lines() {
+    let text = "wrapped lines are moved through one by one";
+
+    let mut editor = Editor::new(text);
+    editor.press(Home).press(Down).type_text("|");
+    assert!(editor.text.contains(" |"));
+    assert!(!editor.text.ends_with('|'));
+
+    let mut editor = Editor::with(text, |editbox| editbox.wrap(false));
+    editor.press(Home).press(Down).type_text("|");
+    assert!(editor.text.ends_with('|'));
+}
+
+#[test]
+fn tab_indents_selected_lines() {
+    let mut editor = Editor::new("ab");
+    editor.press(Enter).type_text("cd");
+
+    editor.press(Home).press(Right).press(Tab);
+    assert_eq!(editor.text, "ab\nc   d");
+
+    editor.ctrl(A).press(Tab);
+    assert_eq!(editor.text, "    ab\n    c   d");
+
+    editor.shift(Tab).shift(Tab);
+    assert_eq!(editor.text, "ab\nc   d");
+
+    // the whole indentation is undone at once
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "    ab\n    c   d");
+}
+
+#[test]
+fn enter_keeps_the_indentation() {
+    let mut editor = Editor::new("    if x {");
+
+    editor.press(Enter).type_text("y");
+    assert_eq!(editor.text, "    if x {\n    y");
+}
+
+#[test]
+fn find_and_replace() {
+    let mut editor = Editor::new("cat hat cat");
+
+    // typing goes into the query while the find bar is open
+    editor.ctrl(F).type_text("cat");
+    assert_eq!(editor.text, "cat hat cat");
+
+    editor.ctrl(H).type_text("dog").press(Enter);
+    assert_eq!(editor.text, "dog hat cat");
+
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "cat hat cat");
+
+    // replacing all is a single undo step
+    editor.ctrl(Enter);
+    assert_eq!(editor.text, "dog hat dog");
+
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "cat hat cat");
+
+    // escape closes the bar but keeps the focus
+    editor.press(Escape).type_text("!");
+    assert_eq!(editor.text, "cat hat cat!");
+    assert!(!editor.events().cancelled);
+}
+
+#[test]
+fn undo_and_redo() {
+    let mut editor = Editor::new("ñandú");
+
+    editor.press(Backspace).press(Backspace);
+    assert_eq!(editor.text, "ñan");
+
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "ñandú");
+
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "");
+
+    editor.ctrl(Y).ctrl(Y);
+    assert_eq!(editor.text, "ñan");
+}
+
+#[test]
+fn undo_steps_are_about_a_word_long() {
+    let mut editor = Editor::new("hello world");
+
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "hello ");
+
+    editor.ctrl(Y);
+
+    // Backspace runs stop at the word beginning too
+    for _ in 0..7 {
+        editor.press(Backspace);
+    }
+    assert_eq!(editor.text, "hell");
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "hello");
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "hello world");
+
+    // a paste is a step of its own
+    editor.ctrl(A).ctrl(C).press(End).ctrl(V);
+    assert_eq!(editor.text, "hello worldhello world");
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "hello world");
+}
+
+#[test]
+fn history_is_cleared_when_the_text_changes_outside() {
+    let mut editor = Editor::new("ab");
+
+    editor.text = "é".to_string();
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "é");
+}
+
+#[test]
+fn bounded_history_can_be_read_and_cleared() {
+    let mut editor = Editor::with("one two three", |editbox| editbox.undo_limit(2));
+
+    let history = editor.harness.ui().editbox_history(editor.id);
+    let typed = |position, inserted: &str| TextEdit {
+        position,
+        removed: String::new(),
+        inserted: inserted.to_string(),
+    };
+    assert_eq!(history.undo.len(), 2);
+    assert_eq!(
+        history.undo[1],
+        vec![
+            typed(8, "t"),
+            typed(9, "h"),
+            typed(10, "r"),
+            typed(11, "e"),
+            typed(12, "e")
+        ]
+    );
+    assert!(history.redo.is_empty());
+
+    editor.ctrl(Z).ctrl(Z).ctrl(Z);
+    assert_eq!(editor.text, "one ");
+
+    editor.harness.ui().clear_editbox_history(editor.id);
+    assert!(editor
+        .harness
+        .ui()
+        .editbox_history(editor.id)
+        .redo
+        .is_empty());
+    editor.ctrl(Y);
+    assert_eq!(editor.text, "one ");
+}
+
+struct Clipboard(String);
+
+impl ClipboardObject for Clipboard {
+    fn get(&self) -> Option<String> {
+        Some(self.0.clone())
+    }
+
+    fn set(&mut self, data: &str) {
+        self.0 = data.to_owned();
+    }
+}
+
+#[test]
+fn password_can_not_be_copied_or_cut() {
+    let mut editor = Editor::with("secret", |editbox| editbox.password(true));
+    editor
+        .harness
+        .ui()
+        .set_clipboard_object(Clipboard("kept".to_string()));
+
+    // the clipboard keeps what was there before
+    editor.ctrl(A).ctrl(C).press(End).ctrl(V);
+    assert_eq!(editor.text, "secretkept");
+
+    editor.ctrl(A).ctrl(X);
+    assert_eq!(editor.text, "secretkept");
+}
+
+#[test]
+fn password_has_no_find_bar() {
+    let mut editor = Editor::with("hunter2", |editbox| editbox.password(true));
+
+    // with the find bar open the typing would go to its query, showing the password
+    editor.ctrl(A).ctrl(F).type_text("x");
+    assert_eq!(editor.text, "x");
+
+    editor.ctrl(A).ctrl(H).type_text("y");
+    assert_eq!(editor.text, "y");
+}
+
+#[test]
+fn password_is_not_revealed_by_a_click_on_another_window() {
+    let mut password = "hunter2".to_string();
+    let mut windows = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 40.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                InputText::new(hash!())
+                    .password(true)
+                    .reveal_toggle()
+                    .ui(ui, &mut password);
+            });
+        Window::new(hash!(), Vector2::new(100., 60.), Vector2::new(90., 30.))
+            .titlebar(false)
+            .ui(ui, |_| {});
+    };
+    let mut harness = Harness::new(200, 100);
+    let (other_window, toggle) = (Vector2::new(150., 75.), Vector2::new(178., 20.));
+
+    harness.frame(&[], &mut windows);
+    harness.frame(&[Event::MouseMove(other_window)], &mut windows);
+    harness.frame(&[Event::MouseDown(other_window)], &mut windows);
+    let before = harness.render();
+
+    // the button is pressed over the other window and released over the reveal toggle
+    harness.frame(&[Event::MouseMove(toggle)], &mut windows);
+    harness.frame(&[Event::MouseUp(toggle)], &mut windows);
+    harness.frame(&[Event::MouseMove(other_window)], &mut windows);
+    harness.frame(&[], &mut windows);
+
+    assert!(harness.render() == before, "the password was revealed");
+}
+
+#[test]
+fn validation_error_row_does_not_move_the_widgets_below() {
+    let validate_port = |port: &str| match port.parse::<u16>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Port should be 0..65535".to_string()),
+    };
+    let mut harness = Harness::new(200, 100);
+    let mut below = vec![];
+
+    for port in &["99999", "8080"] {
+        let mut port = port.to_string();
+        harness.frame(&[], |ui| {
+            Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 80.))
+                .titlebar(false)
+                .ui(ui, |ui| {
+                    InputText::new(hash!())
+                        .validate(&validate_port)
+                        .ui(ui, &mut port);
+                    below.push(ui.canvas().cursor());
+                });
+        });
+    }
+
+    assert_eq!(below[0].y, below[1].y);
+}
+
+#[test]
+fn max_length_limits_typing_and_pasting() {
+    let mut editor = Editor::with("ünïcödé", |editbox| editbox.max_length(9));
+    assert_eq!(editor.text, "ünïcödé");
+
+    editor.type_text("abc");
+    assert_eq!(editor.text, "ünïcödéab");
+
+    editor
+        .press(Home)
+        .shift(Right)
+        .shift(Right)
+        .shift(Right)
+        .ctrl(C);
+    editor.press(Backspace).press(Delete);
+    assert_eq!(editor.text, "ödéab");
+
+    // the second paste fits only partially
+    editor.ctrl(V).ctrl(V);
+    assert_eq!(editor.text, "ünïüödéab");
+}
+
+#[test]
+fn submit_cancel_and_focus_events() {
+    let mut editor = Editor::with("", |editbox| editbox.multiline(false));
+    assert_eq!(
+        editor.events(),
+        EditboxResponse {
+            gained_focus: true,
+            ..Default::default()
+        }
+    );
+
+    editor.type_text("ls");
+    assert!(editor.events().edited);
+
+    // moving the cursor is not an edit
+    editor.press(Left);
+    assert!(!editor.events().edited);
+
+    editor.press(Enter);
+    assert_eq!(editor.text, "ls");
+    assert!(editor.events().submitted);
+
+    editor.press(Escape);
+    let events = editor.events();
+    assert!(events.cancelled && events.lost_focus);
+
+    editor.type_text("x");
+    assert_eq!(editor.text, "ls");
+}
+
+#[test]
+fn multiline_submits_with_ctrl_enter() {
+    let mut editor = Editor::new("a");
+
+    editor.press(Enter);
+    assert_eq!(editor.text, "a\n");
+    assert!(!editor.events().submitted);
+
+    editor.ctrl(Enter);
+    assert_eq!(editor.text, "a\n");
+    assert!(editor.events().submitted);
+}
+
+#[test]
+fn syntax_highlighter_ranges() {
+    let keyword = Color::from_rgb(255, 0, 0);
+    let highlighter = SyntaxHighlighter::new(&["fn", "let"])
+        .line_comment("//")
+        .keyword_color(keyword);
+    let text = r#"let x = 4.5; // "note"
+fn s() { "a \" b" }"#;
+
+    let ranges = highlighter.highlight(text);
+    let highlighted: Vec<_> = ranges
+        .iter()
+        .map(|(range, _)| &text[range.clone()])
+        .collect();
+    assert_eq!(
+        highlighted,
+        ["let", "4.5", r#"// "note""#, "fn", r#""a \" b""#]
+    );
+    assert_eq!(ranges[0].1, keyword);
+}
+
+fn commands(word: &str) -> Vec<String> {
+    ["hello", "help", "history"]
+        .iter()
+        .filter(|command| command.starts_with(word))
+        .map(|command| command.to_string())
+        .collect()
+}
+
+#[test]
+fn completion_popup() {
+    assert_eq!(commands.complete("he"), vec!["hello", "help"]);
+
+    let mut editor = Editor::with("", |editbox| editbox.multiline(false).completer(&commands));
+
+    // Down chooses the second suggestion, Enter accepts it instead of submitting
+    editor.type_text("he").press(Down).press(Enter);
+    assert_eq!(editor.text, "help");
+    assert!(!editor.events().submitted);
+
+    // the whole word is replaced as one undo step
+    editor.ctrl(Z);
+    assert_eq!(editor.text, "he");
+
+    // Escape closes the popup, the editbox keeps the focus
+    editor.type_text("l").press(Escape).press(Enter);
+    assert_eq!(editor.text, "hel");
+    let events = editor.events();
+    assert!(events.submitted && !events.cancelled);
+
+    // Tab accepts too
+    editor.type_text(" hi").press(Tab);
+    assert_eq!(editor.text, "hel history");
+}
+
+#[test]
+fn completion_is_dropped_when_the_text_changes_outside() {
+    let mut editor = Editor::with("", |editbox| editbox.multiline(false).completer(&commands));
+
+    editor.type_text("xx he");
+    editor.text = "a".to_string();
+    editor.press(Tab);
+    assert_eq!(editor.text, "a");
+
+    editor.type_text(" he");
+    editor.text.clear();
+    editor.press(Tab);
+    assert_eq!(editor.text, "");
+}
@@ -0,0 +1,151 @@
+use megaui::{
+    raster::{Image, Rasterizer},
+    Color, DrawList, Rect, Ui, Vertex,
+};
+
+/// Texture id the rasterizer knows nothing about, sampled as plain white
+const UNTEXTURED: u32 = 1000;
+
+fn vertex(x: f32, y: f32, u: f32, v: f32, color: [f32; 4]) -> Vertex {
+    Vertex {
+        pos: [x, y, 0.],
+        uv: [u, v],
+        color,
+    }
+}
+
+/// Two triangles sharing the diagonal, with the whole texture stretched over the rect
+fn quad(rect: Rect, color: [f32; 4], texture: u32) -> DrawList {
+    let mut draw_list = DrawList::new();
+    let (left, top, right, bottom) = (rect.x, rect.y, rect.x + rect.w, rect.y + rect.h);
+
+    draw_list.vertices = vec![
+        vertex(left, top, 0., 0., color),
+        vertex(right, top, 1., 0., color),
+        vertex(right, bottom, 1., 1., color),
+        vertex(left, bottom, 0., 1., color),
+    ];
+    draw_list.indices = vec![0, 1, 2, 0, 2, 3];
+    draw_list.texture = Some(texture);
+    draw_list
+}
+
+fn render(ui: &Ui, draw_lists: &[DrawList], textures: &[(u32, &Image)]) -> Image {
+    let mut image = Image::new(8, 8);
+    image.fill(Color::from_rgb(255, 255, 255));
+
+    let mut rasterizer = Rasterizer::new(&ui.font_atlas);
+    for (id, texture) in textures {
+        rasterizer = rasterizer.texture(*id, texture);
+    }
+    rasterizer.render(draw_lists, &mut image);
+
+    image
+}
+
+fn covered(image: &Image) -> Vec<(u32, u32)> {
+    let mut pixels = vec![];
+    for y in 0..image.height {
+        for x in 0..image.width {
+            if image.pixel(x, y) != [255, 255, 255, 255] {
+                pixels.push((x, y));
+            }
+        }
+    }
+    pixels
+}
+
+#[test]
+fn quad_covers_its_pixels_once() {
+    let ui = Ui::new();
+    // half transparent, a pixel of the shared edge drawn twice would come out darker
+    let draw_list = quad(Rect::new(2., 2., 4., 3.), [0., 0., 0., 0.5], UNTEXTURED);
+    let image = render(&ui, &[draw_list], &[]);
+
+    let expected: Vec<(u32, u32)> = (2..5).flat_map(|y| (2..6).map(move |x| (x, y))).collect();
+    assert_eq!(covered(&image), expected);
+    for (x, y) in expected {
+        assert_eq!(image.pixel(x, y), [128, 128, 128, 255]);
+    }
+}
+
+#[test]
+fn pixel_centers_decide_the_coverage() {
+    let ui = Ui::new();
+    // covers the centers of the pixels 1 and 2 only
+    let draw_list = quad(Rect::new(0.6, 0.6, 2.3, 1.), [0., 0., 0., 1.], UNTEXTURED);
+    let image = render(&ui, &[draw_list], &[]);
+
+    assert_eq!(covered(&image), vec![(1, 1), (2, 1)]);
+}
+
+#[test]
+fn texture_is_sampled_nearest() {
+    let ui = Ui::new();
+    let red = [255, 0, 0, 255];
+    let green = [0, 255, 0, 255];
+    let blue = [0, 0, 255, 255];
+    let black = [0, 0, 0, 255];
+    let texture = Image::from_rgba(2, 2, [red, green, blue, black].concat());
+
+    let draw_list = quad(Rect::new(0., 0., 4., 4.), [1., 1., 1., 1.], 7);
+    let image = render(&ui, &[draw_list], &[(7, &texture)]);
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let expected = match (x < 2, y < 2) {
+                (true, true) => red,
+                (false, true) => green,
+                (true, false) => blue,
+                (false, false) => black,
+            };
+            assert_eq!(image.pixel(x, y), expected, "pixel {} {}", x, y);
+        }
+    }
+}
+
+#[test]
+fn vertex_color_multiplies_the_texture() {
+    let ui = Ui::new();
+    let texture = Image::from_rgba(1, 1, vec![255, 128, 0, 255]);
+
+    let draw_list = quad(Rect::new(0., 0., 2., 2.), [0.5, 1., 1., 1.], 7);
+    let image = render(&ui, &[draw_list], &[(7, &texture)]);
+
+    assert_eq!(image.pixel(0, 0), [128, 128, 0, 255]);
+}
+
+#[test]
+fn clipping_zone_limits_the_pixels() {
+    let ui = Ui::new();
+    let mut draw_list = quad(Rect::new(0., 0., 8., 8.), [0., 0., 0., 1.], UNTEXTURED);
+    draw_list.clipping_zone = Some(Rect::new(3., 4., 2., 1.));
+    let image = render(&ui, &[draw_list], &[]);
+
+    assert_eq!(covered(&image), vec![(3, 4), (4, 4)]);
+}
+
+#[test]
+fn qoi_keeps_every_pixel() {
+    let mut image = Image::new(70, 3);
+    for x in 0..70 {
+        // a run longer than the QOI run limit, then small and big color steps
+        image.set_pixel(x, 0, [10, 20, 30, 255]);
+        image.set_pixel(x, 1, [x as u8, 2 * x as u8, 200 - x as u8, 255]);
+        image.set_pixel(
+            x,
+            2,
+            [
+                (x * 37) as u8,
+                (x * 91) as u8,
+                (x * 13) as u8,
+                (x * 7) as u8,
+            ],
+        );
+    }
+
+    let qoi = image.to_qoi();
+    assert!(qoi.len() < image.data.len());
+    assert_eq!(Image::from_qoi(&qoi), Some(image));
+    assert_eq!(Image::from_qoi(&qoi[..qoi.len() / 2]), None);
+}
@@ -0,0 +1,709 @@
+use megaui::{
+    hash,
+    raster::Image,
+    snapshot::{assert_snapshot, Event, Harness},
+    widgets::{
+        Button, ComboBox, Editbox, Group, InputText, Label, RichText, Span, SyntaxHighlighter,
+        Tabbar, Window,
+    },
+    Aligment, Color, DrawLayer, Gradient, KeyCode, Layout, Rect, Style, Ui, Vector2,
+    VerticalAligment,
+};
+
+fn snapshot_path(name: &str) -> String {
+    format!(
+        "{}/tests/snapshots/{}.qoi",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    )
+}
+
+#[test]
+fn missing_reference_fails() {
+    // blessing creates the missing references instead
+    if std::env::var("MEGAUI_BLESS").map_or(false, |bless| bless == "1") {
+        return;
+    }
+
+    let path = snapshot_path("missing");
+    let result = std::panic::catch_unwind(|| assert_snapshot(&Image::new(1, 1), &path, 0));
+
+    assert!(result.is_err());
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+fn widgets(ui: &mut Ui) {
+    Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 120.))
+        .label("Widgets")
+        .ui(ui, |ui| {
+            ui.label(None, "Label");
+            ui.button(None, "Button");
+            ui.separator();
+            ui.checkbox(hash!(), "Checkbox", &mut true);
+        });
+}
+
+#[test]
+fn window_with_widgets() {
+    let mut harness = Harness::new(200, 140);
+
+    harness.frame(&[], widgets);
+    harness.frame(&[], widgets);
+
+    assert_snapshot(&harness.render(), snapshot_path("window_with_widgets"), 2);
+}
+
+#[test]
+fn hovered_button() {
+    let mut harness = Harness::new(200, 140);
+
+    harness.frame(&[], widgets);
+    harness.frame(&[Event::MouseMove(Vector2::new(20., 50.))], widgets);
+
+    assert_snapshot(&harness.render(), snapshot_path("hovered_button"), 2);
+}
+
+#[test]
+fn editbox_typing() {
+    let mut text = String::new();
+    let mut editbox = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 60.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                ui.editbox(hash!(), Vector2::new(150., 40.), &mut text);
+            });
+    };
+
+    let mut harness = Harness::new(200, 80);
+    let click = Vector2::new(30., 30.);
+
+    harness.frame(&[], &mut editbox);
+    harness.frame(&[Event::MouseDown(click)], &mut editbox);
+    harness.frame(&[Event::MouseUp(click)], &mut editbox);
+    harness.frame(
+        &"megaui".chars().map(Event::Char).collect::<Vec<_>>(),
+        &mut editbox,
+    );
+    harness.frame(&[], &mut editbox);
+
+    assert_snapshot(&harness.render(), snapshot_path("editbox_typing"), 2);
+}
+
+#[test]
+fn horizontal_groups() {
+    let groups = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 120.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                for i in 0..5 {
+                    Group::new(hash!("group", i), Vector2::new(50., 40.))
+                        .layout(Layout::Horizontal)
+                        .ui(ui, |ui| {
+                            ui.label(None, &format!("#{}", i));
+                        });
+                }
+            });
+    };
+
+    let mut harness = Harness::new(200, 140);
+
+    harness.frame(&[], groups);
+    harness.frame(&[], groups);
+
+    assert_snapshot(&harness.render(), snapshot_path("horizontal_groups"), 2);
+}
+
+#[test]
+fn nested_groups_in_scrolled_window() {
+    let nested = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                for i in 0..4 {
+                    Group::new(hash!("outer", i), Vector2::new(160., 60.)).ui(ui, |ui| {
+                        Group::new(hash!("inner", i), Vector2::new(140., 80.)).ui(ui, |ui| {
+                            for j in 0..5 {
+                                ui.label(None, &format!("line {}", j));
+                            }
+                        });
+                    });
+                }
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+    let inside = Vector2::new(100., 60.);
+
+    harness.frame(&[], nested);
+    harness.frame(&[Event::MouseMove(inside)], nested);
+    harness.frame(&[Event::MouseDown(inside), Event::MouseUp(inside)], nested);
+    harness.frame(&[Event::MouseWheel(0., 1.)], nested);
+    harness.frame(&[], nested);
+
+    assert_snapshot(
+        &harness.render(),
+        snapshot_path("nested_groups_in_scrolled_window"),
+        2,
+    );
+}
+
+#[test]
+fn draw_layers() {
+    let layers = |ui: &mut Ui| {
+        ui.global_canvas(DrawLayer::Background).rect(
+            Rect::new(0., 0., 200., 20.),
+            None,
+            Color::from_rgb(100, 100, 200),
+        );
+        ui.global_canvas(DrawLayer::Foreground).line(
+            Vector2::new(0., 0.),
+            Vector2::new(200., 120.),
+            Color::from_rgb(200, 0, 0),
+        );
+
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                ui.canvas().layer(DrawLayer::Foreground).rect(
+                    Rect::new(20., 20., 80., 30.),
+                    None,
+                    Color::from_rgb(0, 150, 0),
+                );
+                Group::new(hash!(), Vector2::new(100., 60.)).ui(ui, |ui| {
+                    ui.label(None, "below");
+                });
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+
+    harness.frame(&[], layers);
+    harness.frame(&[], layers);
+
+    assert_snapshot(&harness.render(), snapshot_path("draw_layers"), 2);
+}
+
+#[test]
+fn window_shadows() {
+    let windows = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(100., 60.))
+            .label("Back")
+            .ui(ui, |ui| ui.label(None, "inactive"));
+        Window::new(hash!(), Vector2::new(60., 40.), Vector2::new(100., 60.))
+            .label("Front")
+            .background_alpha(0.7)
+            .ui(ui, |ui| ui.label(None, "focused"));
+    };
+
+    let mut harness = Harness::new(200, 120);
+    harness.ui().set_style(Style {
+        window_shadow_size: 5.,
+        ..Style::default()
+    });
+
+    harness.frame(&[], windows);
+    harness.frame(&[], windows);
+    let image = harness.render();
+
+    // translucent windows are not darkened by their own shadows
+    assert_eq!(image.pixel(100, 80), [243, 243, 243, 255]);
+    assert_eq!(image.pixel(40, 40), [246, 246, 246, 255]);
+
+    assert_snapshot(&image, snapshot_path("window_shadows"), 2);
+}
+
+#[test]
+fn gradients() {
+    let gradients = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .label("Gradients")
+            .ui(ui, |ui| {
+                let mut canvas = ui.canvas();
+                let cursor = canvas.cursor();
+
+                canvas.rect_gradient(
+                    Rect::new(cursor.x, cursor.y, 50., 40.),
+                    Gradient::vertical(Color::from_rgb(255, 0, 0), Color::from_rgb(0, 0, 255)),
+                );
+                canvas.rect_gradient(
+                    Rect::new(cursor.x + 55., cursor.y, 50., 40.),
+                    Gradient::horizontal(Color::from_rgb(0, 0, 0), Color::from_rgba(0, 0, 0, 0)),
+                );
+                canvas.rect_gradient(
+                    Rect::new(cursor.x + 110., cursor.y, 50., 40.),
+                    Gradient::Corners {
+                        top_left: Color::from_rgb(255, 255, 255),
+                        top_right: Color::from_rgb(255, 0, 0),
+                        bottom_right: Color::from_rgb(0, 0, 0),
+                        bottom_left: Color::from_rgb(0, 0, 0),
+                    },
+                );
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+    harness.ui().set_style(Style {
+        title_background_focused: Some(Gradient::vertical(
+            Color::from_rgb(200, 210, 240),
+            Color::from_rgb(150, 170, 220),
+        )),
+        ..Style::default()
+    });
+
+    harness.frame(&[], gradients);
+    harness.frame(&[], gradients);
+
+    assert_snapshot(&harness.render(), snapshot_path("gradients"), 2);
+}
+
+#[test]
+fn multiple_fonts() {
+    let mut harness = Harness::new(200, 120);
+    let big = harness
+        .ui()
+        .load_font(include_bytes!("../assets/ProggyClean.ttf"), 26)
+        .unwrap();
+
+    let fonts = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                ui.push_font(big);
+                ui.label(None, "Heading");
+                ui.pop_font();
+                ui.label(None, "Regular text");
+                ui.push_font(big);
+                ui.button(None, "Big");
+                ui.pop_font();
+            });
+    };
+
+    harness.frame(&[], fonts);
+    harness.frame(&[], fonts);
+
+    assert_snapshot(&harness.render(), snapshot_path("multiple_fonts"), 2);
+}
+
+#[test]
+fn unicode_label() {
+    let labels = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 60.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                ui.label(None, "café ñandú über € 100");
+                // not in the font, drawn with the "missing glyph"
+                ui.label(None, "日本");
+            });
+    };
+
+    let mut harness = Harness::new(200, 80);
+
+    harness.frame(&[], labels);
+    harness.frame(&[], labels);
+
+    assert_snapshot(&harness.render(), snapshot_path("unicode_label"), 2);
+}
+
+#[test]
+fn wrapped_labels() {
+    let labels = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 140.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                Label::new("The quick brown fox jumps over the lazy dog.\nNew paragraph")
+                    .wrap()
+                    .ui(ui);
+                Label::new("Unbreakable_long_identifier_name")
+                    .wrap_width(100.)
+                    .ui(ui);
+                ui.button(None, "After");
+            });
+    };
+
+    let mut harness = Harness::new(200, 160);
+
+    harness.frame(&[], labels);
+    harness.frame(&[], labels);
+
+    assert_snapshot(&harness.render(), snapshot_path("wrapped_labels"), 2);
+}
+
+#[test]
+fn aligned_and_truncated_text() {
+    let widgets = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 130.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                Label::new("Right")
+                    .size(Vector2::new(160., 16.))
+                    .aligment(Aligment::Right)
+                    .ui(ui);
+                Label::new("Centered label")
+                    .size(Vector2::new(160., 16.))
+                    .aligment(Aligment::Center)
+                    .ui(ui);
+                Button::new("A button with a long label")
+                    .size(Vector2::new(90., 24.))
+                    .aligment(Aligment::Center)
+                    .vertical_aligment(VerticalAligment::Center)
+                    .ui(ui);
+                ui.tabbar(
+                    hash!(),
+                    Vector2::new(160., 20.),
+                    &["First", "Second tab", "Third"],
+                );
+                ui.combo_box(
+                    hash!(),
+                    "Long combo label",
+                    &["Variant number one", "Two"],
+                    None,
+                );
+            });
+    };
+
+    let mut harness = Harness::new(260, 160);
+    let on_button = Vector2::new(40., 60.);
+
+    harness.frame(&[], widgets);
+    harness.frame(&[Event::MouseMove(on_button)], widgets);
+
+    assert_snapshot(
+        &harness.render(),
+        snapshot_path("aligned_and_truncated_text"),
+        2,
+    );
+}
+
+#[test]
+fn vertically_aligned_tabbar_and_combobox() {
+    let mut selected = 0;
+    let mut widgets = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 120.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                Tabbar::new(hash!(), Vector2::new(160., 40.), &["Top", "Tabs"]).ui(ui);
+                Tabbar::new(hash!(), Vector2::new(160., 40.), &["Bottom", "Tabs"])
+                    .vertical_aligment(VerticalAligment::Bottom)
+                    .ui(ui);
+                ComboBox::new(hash!(), &["Centered", "Variant"])
+                    .vertical_aligment(VerticalAligment::Center)
+                    .ui(ui, &mut selected);
+            });
+    };
+
+    let mut harness = Harness::new(200, 140);
+    harness.frame(&[], &mut widgets);
+    harness.frame(&[], &mut widgets);
+
+    assert_snapshot(
+        &harness.render(),
+        snapshot_path("vertically_aligned_tabbar_and_combobox"),
+        2,
+    );
+}
+
+#[test]
+fn rich_text() {
+    let mut harness = Harness::new(220, 120);
+    let big = harness
+        .ui()
+        .load_font(include_bytes!("../assets/ProggyClean.ttf"), 26)
+        .unwrap();
+
+    let text = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(200., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                ui.rich_text(None, "[color=#c80000][ERROR][/] file [u]not[/] found");
+                ui.rich_text(None, "[[x] [bg=#ffff00]hit[/] and [unknown] tag");
+                RichText::new()
+                    .span(Span::new("Big").font(big).color(Color::from_rgb(0, 0, 200)))
+                    .text(" small\nsecond ")
+                    .span(Span::new("line").underline())
+                    .ui(ui);
+            });
+    };
+
+    harness.frame(&[], text);
+    harness.frame(&[], text);
+
+    assert_snapshot(&harness.render(), snapshot_path("rich_text"), 2);
+}
+
+#[test]
+fn scaled_ui() {
+    let mut harness = Harness::new(400, 280);
+    harness.ui().set_scale(2.);
+
+    harness.frame(&[], widgets);
+    // input is in the screen pixels: over the button at (20, 50) UI pixels
+    harness.frame(&[Event::MouseMove(Vector2::new(40., 100.))], widgets);
+
+    assert_snapshot(&harness.render(), snapshot_path("scaled_ui"), 2);
+}
+
+#[test]
+fn password_input() {
+    let mut password = "hunter2".to_string();
+    let mut api_key = "sk-12345".to_string();
+    let mut inputs = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 60.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                InputText::new(hash!())
+                    .label("Password")
+                    .password(true)
+                    .reveal_toggle()
+                    .ui(ui, &mut password);
+                InputText::new(hash!())
+                    .label("API key")
+                    .password(true)
+                    .reveal_toggle()
+                    .ui(ui, &mut api_key);
+            });
+    };
+
+    let mut harness = Harness::new(200, 80);
+    // the second input's reveal toggle
+    let toggle = Vector2::new(94., 44.);
+
+    harness.frame(&[], &mut inputs);
+    harness.frame(&[Event::MouseDown(toggle)], &mut inputs);
+    harness.frame(&[Event::MouseUp(toggle)], &mut inputs);
+    harness.frame(&[Event::MouseMove(Vector2::new(0., 0.))], &mut inputs);
+
+    assert_snapshot(&harness.render(), snapshot_path("password_input"), 2);
+}
+
+#[test]
+fn input_placeholder_and_validation() {
+    let mut name = String::new();
+    let mut port = "99999".to_string();
+    let validate_port = |port: &str| match port.parse::<u16>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Port should be 0..65535".to_string()),
+    };
+    let mut inputs = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                InputText::new(hash!())
+                    .label("Name")
+                    .placeholder("John Doe")
+                    .ui(ui, &mut name);
+                InputText::new(hash!())
+                    .label("Port")
+                    .validate(&validate_port)
+                    .ui(ui, &mut port);
+                ui.label(None, "After");
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+
+    harness.frame(&[], &mut inputs);
+    harness.frame(&[], &mut inputs);
+
+    assert_snapshot(
+        &harness.render(),
+        snapshot_path("input_placeholder_and_validation"),
+        2,
+    );
+}
+
+#[test]
+fn editbox_wrap_and_scroll() {
+    let mut notes = "Long lines are wrapped at the editbox width instead of being cut".to_string();
+    let mut command = "cargo test --workspace --all-targets".to_string();
+    let mut editboxes = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                Editbox::new(hash!(), Vector2::new(150., 50.)).ui(ui, &mut notes);
+                Editbox::new(hash!(), Vector2::new(150., 20.))
+                    .multiline(false)
+                    .ui(ui, &mut command);
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+    let click = Vector2::new(30., 75.);
+
+    harness.frame(&[], &mut editboxes);
+    harness.frame(&[Event::MouseDown(click)], &mut editboxes);
+    harness.frame(&[Event::MouseUp(click)], &mut editboxes);
+    harness.frame(&[Event::Key(KeyCode::End)], &mut editboxes);
+
+    assert_snapshot(
+        &harness.render(),
+        snapshot_path("editbox_wrap_and_scroll"),
+        2,
+    );
+}
+
+#[test]
+fn highlighted_editbox() {
+    let mut shader =
+        "// tint\nuniform float k;\nvoid main() {\n  c = \"rgb\" * 0.5;\n}".to_string();
+    let keywords = ["uniform", "float", "void"];
+    let highlighter = SyntaxHighlighter::new(&keywords).line_comment("//");
+    let mut editbox = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                Editbox::new(hash!(), Vector2::new(160., 80.))
+                    .highlighter(&highlighter)
+                    .ui(ui, &mut shader);
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+
+    harness.frame(&[], &mut editbox);
+    harness.frame(&[], &mut editbox);
+
+    assert_snapshot(&harness.render(), snapshot_path("highlighted_editbox"), 2);
+}
+
+#[test]
+fn code_editor() {
+    let mut code = "fn main() {\n    let v = [1, 2];\n}".to_string();
+    let mut editbox = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                Editbox::new(hash!(), Vector2::new(160., 80.))
+                    .line_numbers(true)
+                    .match_brackets(true)
+                    .ui(ui, &mut code);
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+    let click = Vector2::new(60., 22.);
+
+    harness.frame(&[], &mut editbox);
+    harness.frame(&[Event::MouseDown(click)], &mut editbox);
+    harness.frame(&[Event::MouseUp(click)], &mut editbox);
+    harness.frame(&[Event::Key(KeyCode::End)], &mut editbox);
+
+    assert_snapshot(&harness.render(), snapshot_path("code_editor"), 2);
+}
+
+#[test]
+fn find_bar() {
+    let mut code = (1..=10)
+        .map(|i| format!("let x{} = {};", i, i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut editbox = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                Editbox::new(hash!(), Vector2::new(160., 90.)).ui(ui, &mut code);
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+    let click = Vector2::new(30., 22.);
+    let ctrl = |key| Event::KeyWithModifiers(key, false, true);
+
+    harness.frame(&[], &mut editbox);
+    harness.frame(&[Event::MouseDown(click)], &mut editbox);
+    harness.frame(&[Event::MouseUp(click)], &mut editbox);
+    // open the replace bar, search for "1" and go to its third occurrence
+    for event in &[
+        ctrl(KeyCode::H),
+        Event::Key(KeyCode::Tab),
+        Event::Char('1'),
+        Event::Key(KeyCode::Enter),
+        Event::Key(KeyCode::Enter),
+    ] {
+        harness.frame(std::slice::from_ref(event), &mut editbox);
+        harness.frame(&[], &mut editbox);
+    }
+
+    // the "1" of "x10" is selected and scrolled into view at the bottom line,
+    // the "1" of "10" on the same line has the match background
+    assert_snapshot(&harness.render(), snapshot_path("find_bar"), 2);
+}
+
+#[test]
+fn completion_popup() {
+    let mut command = String::new();
+    let complete = |word: &str| -> Vec<String> {
+        ["spawn", "speed", "spectate", "stats"]
+            .iter()
+            .filter(|command| command.starts_with(word))
+            .map(|command| command.to_string())
+            .collect()
+    };
+    let mut console = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .label("Console")
+            .ui(ui, |ui| {
+                InputText::new(hash!())
+                    .completer(&complete)
+                    .ui(ui, &mut command);
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+    let click = Vector2::new(30., 40.);
+
+    harness.frame(&[], &mut console);
+    harness.frame(&[Event::MouseDown(click)], &mut console);
+    harness.frame(&[Event::MouseUp(click)], &mut console);
+    // type "sp" and choose the second suggestion
+    for event in &[
+        Event::Char('s'),
+        Event::Char('p'),
+        Event::Key(KeyCode::Down),
+    ] {
+        harness.frame(std::slice::from_ref(event), &mut console);
+        harness.frame(&[], &mut console);
+    }
+
+    assert_snapshot(&harness.render(), snapshot_path("completion_popup"), 2);
+}
+
+#[test]
+fn completion_in_popup() {
+    let mut command = String::new();
+    let complete = |word: &str| -> Vec<String> {
+        ["spawn", "speed", "spectate", "stats"]
+            .iter()
+            .filter(|command| command.starts_with(word))
+            .map(|command| command.to_string())
+            .collect()
+    };
+    let mut console = |ui: &mut Ui| {
+        Window::new(hash!(), Vector2::new(10., 10.), Vector2::new(180., 100.))
+            .titlebar(false)
+            .ui(ui, |ui| {
+                ui.popup(hash!(), Vector2::new(160., 60.), |ui| {
+                    Editbox::new(hash!(), Vector2::new(150., 20.))
+                        .multiline(false)
+                        .completer(&complete)
+                        .ui(ui, &mut command);
+                    ui.label(None, "in the popup");
+                });
+            });
+    };
+
+    let mut harness = Harness::new(200, 120);
+    let click = Vector2::new(30., 20.);
+
+    harness.frame(&[], &mut console);
+    harness.frame(&[Event::MouseDown(click)], &mut console);
+    harness.frame(&[Event::MouseUp(click)], &mut console);
+    harness.frame(&[Event::Char('s'), Event::Char('p')], &mut console);
+    harness.frame(&[], &mut console);
+
+    // the suggestions are drawn over the popup, which keeps its place and content
+    assert_snapshot(&harness.render(), snapshot_path("completion_in_popup"), 2);
+
+    harness.frame(&[Event::Key(KeyCode::Tab)], &mut console);
+    harness.frame(&[Event::Char('!')], &mut console);
+    assert_eq!(command, "spawn!");
+}